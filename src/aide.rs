@@ -0,0 +1,411 @@
+//! [`aide`] integration: responses for handlers returning [`Brest`] envelopes, and the fail
+//! responses produced by the extractors in [`crate::extractors`].
+
+use aide::generate::GenContext;
+use aide::openapi::{MediaType, Operation, ReferenceOr, Response, StatusCode};
+use aide::OperationOutput;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::debug::DebugInfo;
+use crate::Brest;
+
+const JSON: &str = "application/json";
+
+fn tag(kind: &'static str) -> SchemaObject {
+    let mut object = ObjectValidation::default();
+    object.properties.insert(
+        "type".to_string(),
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec![kind.into()]),
+            ..Default::default()
+        }
+        .into(),
+    );
+    object.required.insert("type".to_string());
+
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(object)),
+        ..Default::default()
+    };
+    schema.metadata().title = Some(kind.to_string());
+    schema
+}
+
+fn property(schema: &mut SchemaObject, name: &str, property: Schema, required: bool) {
+    let object = schema.object();
+    object.properties.insert(name.to_string(), property);
+    if required {
+        object.required.insert(name.to_string());
+    }
+}
+
+/// Schema of the `success` variant of a `Brest<D, _, Meta>`.
+pub fn success_schema<D: JsonSchema, Meta: JsonSchema>(gen: &mut SchemaGenerator) -> SchemaObject {
+    let mut schema = tag("success");
+    property(&mut schema, "data", gen.subschema_for::<D>(), true);
+    property(&mut schema, "meta", gen.subschema_for::<Meta>(), false);
+    schema
+}
+
+fn failure_schema<C: JsonSchema, Meta: JsonSchema>(
+    gen: &mut SchemaGenerator,
+    kind: &'static str,
+) -> SchemaObject {
+    let mut schema = tag(kind);
+    property(&mut schema, "message", gen.subschema_for::<String>(), true);
+    property(&mut schema, "code", gen.subschema_for::<C>(), false);
+    property(&mut schema, "debug", gen.subschema_for::<DebugInfo>(), false);
+    property(&mut schema, "meta", gen.subschema_for::<Meta>(), false);
+    schema
+}
+
+/// Schema of the `error` variant of a `Brest<_, C, Meta>`.
+pub fn error_schema<C: JsonSchema, Meta: JsonSchema>(gen: &mut SchemaGenerator) -> SchemaObject {
+    failure_schema::<C, Meta>(gen, "error")
+}
+
+/// Schema of the `fail` variant of a `Brest<_, C, Meta>`.
+pub fn fail_schema<C: JsonSchema, Meta: JsonSchema>(gen: &mut SchemaGenerator) -> SchemaObject {
+    failure_schema::<C, Meta>(gen, "fail")
+}
+
+fn json_response(description: &str, schema: SchemaObject) -> Response {
+    Response {
+        description: description.to_string(),
+        content: [(
+            JSON.to_string(),
+            MediaType {
+                schema: Some(aide::openapi::SchemaObject {
+                    json_schema: schema.into(),
+                    example: None,
+                    external_docs: None,
+                }),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
+
+/// Documents a handler returning `Brest<D, C, Meta>`: the success as `200`, fails as `4XX`
+/// and errors as `5XX`.
+impl<D, C, Meta> OperationOutput for Brest<D, C, Meta>
+where
+    D: Serialize + JsonSchema,
+    C: JsonSchema,
+    Meta: JsonSchema,
+{
+    type Inner = D;
+
+    fn operation_response(ctx: &mut GenContext, _operation: &mut Operation) -> Option<Response> {
+        let schema = ctx.schema.subschema_for::<Self>().into_object();
+        Some(json_response("Brest envelope", schema))
+    }
+
+    fn inferred_responses(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Vec<(Option<u16>, Response)> {
+        // Inferred responses only carry exact statuses, the ranges are set on the operation.
+        let responses = &mut operation.responses.get_or_insert_with(Default::default).responses;
+        responses.entry(StatusCode::Range(4)).or_insert_with(|| {
+            ReferenceOr::Item(json_response("Fail", fail_schema::<C, Meta>(&mut ctx.schema)))
+        });
+        responses.entry(StatusCode::Range(5)).or_insert_with(|| {
+            ReferenceOr::Item(json_response("Error", error_schema::<C, Meta>(&mut ctx.schema)))
+        });
+
+        vec![(
+            Some(200),
+            json_response("Success", success_schema::<D, Meta>(&mut ctx.schema)),
+        )]
+    }
+}
+
+#[cfg(feature = "axum")]
+mod extractors {
+    use super::*;
+    use crate::extractors::{
+        Bytes, Extension, Form, IfMatch, IfUnmodifiedSince, Json, MatchedPath, Pagination, Path,
+        Query, RawForm, RawPathParams, RequestId,
+    };
+    use aide::OperationInput;
+
+    /// Builds the responses of an extractor rejecting with `Brest<(), u32, ()>`.
+    fn rejections(ctx: &mut GenContext, fails: &[(u16, &str)]) -> Vec<(Option<u16>, Response)> {
+        fails
+            .iter()
+            .map(|(status, description)| {
+                (
+                    Some(*status),
+                    json_response(description, fail_schema::<u32, ()>(&mut ctx.schema)),
+                )
+            })
+            .collect()
+    }
+
+    const BODY: [(u16, &str); 2] = [
+        (400, "The request body could not be read"),
+        (413, "The request body is too large"),
+    ];
+
+    impl<T: JsonSchema> OperationInput for Json<T> {
+        fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+            axum::Json::<T>::operation_input(ctx, operation);
+        }
+
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(
+                ctx,
+                &[
+                    (400, "The request body could not be read or is not valid JSON"),
+                    (413, "The request body is too large"),
+                    (415, "Missing `application/json` content type"),
+                    (422, "The request body doesn't match the expected shape"),
+                ],
+            )
+        }
+    }
+
+    impl<T: JsonSchema> OperationInput for Form<T> {
+        fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+            axum::extract::Form::<T>::operation_input(ctx, operation);
+        }
+
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(
+                ctx,
+                &[
+                    (400, "The form could not be read or deserialized"),
+                    (413, "The request body is too large"),
+                    (415, "Missing `application/x-www-form-urlencoded` content type"),
+                    (422, "The form body doesn't match the expected shape"),
+                ],
+            )
+        }
+    }
+
+    impl OperationInput for RawForm {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            let mut responses = rejections(ctx, &BODY);
+            responses.extend(rejections(
+                ctx,
+                &[(415, "Missing `application/x-www-form-urlencoded` content type")],
+            ));
+            responses
+        }
+    }
+
+    impl OperationInput for Bytes {
+        fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+            axum::body::Bytes::operation_input(ctx, operation);
+        }
+
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(ctx, &BODY)
+        }
+    }
+
+    impl<T: JsonSchema> OperationInput for Path<T> {
+        fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+            axum::extract::Path::<T>::operation_input(ctx, operation);
+        }
+
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(
+                ctx,
+                &[
+                    (400, "A path parameter could not be deserialized"),
+                    (500, "The route has no path parameters to extract"),
+                ],
+            )
+        }
+    }
+
+    impl OperationInput for RawPathParams {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(
+                ctx,
+                &[
+                    (400, "A path parameter is not valid UTF-8"),
+                    (500, "The route has no path parameters to extract"),
+                ],
+            )
+        }
+    }
+
+    impl<T: JsonSchema> OperationInput for Query<T> {
+        fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+            axum::extract::Query::<T>::operation_input(ctx, operation);
+        }
+
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(ctx, &[(400, "The query string could not be deserialized")])
+        }
+    }
+
+    impl OperationInput for Pagination {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(ctx, &[(400, "Invalid `page` or `per_page`")])
+        }
+    }
+
+    impl<T> OperationInput for Extension<T> {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(ctx, &[(500, "The request extension is missing")])
+        }
+    }
+
+    impl OperationInput for MatchedPath {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(ctx, &[(500, "No route matched the request")])
+        }
+    }
+
+    impl OperationInput for IfMatch {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(
+                ctx,
+                &[
+                    (400, "The `If-Match` header is malformed"),
+                    (412, "The `If-Match` header doesn't match the current entity tag"),
+                    (428, "Missing `If-Match` header"),
+                ],
+            )
+        }
+    }
+
+    impl OperationInput for IfUnmodifiedSince {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            rejections(
+                ctx,
+                &[
+                    (400, "The `If-Unmodified-Since` header is malformed"),
+                    (412, "The resource was modified after `If-Unmodified-Since`"),
+                    (428, "Missing `If-Unmodified-Since` header"),
+                ],
+            )
+        }
+    }
+
+    impl OperationInput for RequestId {
+        fn inferred_early_responses(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            vec![(
+                Some(500),
+                json_response(
+                    "`RequestIdLayer` is not installed",
+                    error_schema::<u32, ()>(&mut ctx.schema),
+                ),
+            )]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aide::generate::in_context;
+
+    fn to_json<T: Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_brest_responses() {
+        let (inferred, operation) = in_context(|ctx| {
+            let mut operation = Operation::default();
+            let inferred = Brest::<String>::inferred_responses(ctx, &mut operation);
+            (inferred, operation)
+        });
+
+        assert_eq!(inferred.len(), 1);
+        let (status, success) = &inferred[0];
+        assert_eq!(*status, Some(200));
+        let schema = to_json(&success.content[JSON].schema);
+        assert_eq!(schema["properties"]["type"]["enum"][0], "success");
+        assert_eq!(schema["properties"]["data"]["type"], "string");
+        assert_eq!(schema["required"], serde_json::json!(["data", "type"]));
+
+        let responses = to_json(&operation.responses);
+        let keys: Vec<_> = responses.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, ["4XX", "5XX"]);
+        assert_eq!(
+            responses["4XX"]["content"][JSON]["schema"]["properties"]["type"]["enum"][0],
+            "fail"
+        );
+        assert_eq!(
+            responses["5XX"]["content"][JSON]["schema"]["properties"]["code"]["type"],
+            "integer"
+        );
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn test_extractor_responses() {
+        use crate::extractors::{IfMatch, Json};
+        use aide::OperationInput;
+
+        let (json, if_match, operation) = in_context(|ctx| {
+            let mut operation = Operation::default();
+            Json::<String>::operation_input(ctx, &mut operation);
+            let json = Json::<String>::inferred_early_responses(ctx, &mut operation);
+            let if_match = IfMatch::inferred_early_responses(ctx, &mut operation);
+            (json, if_match, operation)
+        });
+
+        let statuses: Vec<_> = json.iter().map(|(status, _)| status.unwrap()).collect();
+        assert_eq!(statuses, [400, 413, 415, 422]);
+        let statuses: Vec<_> = if_match.iter().map(|(status, _)| status.unwrap()).collect();
+        assert_eq!(statuses, [400, 412, 428]);
+        assert!(operation.request_body.is_some());
+
+        let fail = to_json(&if_match[1].1.content[JSON].schema);
+        assert_eq!(fail["properties"]["type"]["enum"][0], "fail");
+    }
+}
@@ -0,0 +1,504 @@
+use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "axum")]
+use axum::http::StatusCode;
+
+use crate::Brest;
+
+/// Whether a code is reported as a `fail` (the client's fault) or an `error` (the server's).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeKind {
+    Fail,
+    Error,
+}
+
+/// A documented error code, usually implemented by an enum used as the `C` of [`Brest`].
+pub trait BrestCode: Sized {
+    /// Every code of this type, in the order they are documented.
+    fn all() -> Vec<Self>;
+
+    /// The message used when a response is built from the code alone.
+    fn message(&self) -> Cow<'static, str>;
+
+    /// The HTTP status of responses carrying this code, 400 Bad Request unless overridden.
+    fn status(&self) -> u16 {
+        400
+    }
+
+    /// [`CodeKind::Error`] for 5xx statuses, [`CodeKind::Fail`] otherwise.
+    fn kind(&self) -> CodeKind {
+        if self.status() >= 500 {
+            CodeKind::Error
+        } else {
+            CodeKind::Fail
+        }
+    }
+
+    /// A longer explanation for the catalog, e.g. what the client should do about it.
+    fn description(&self) -> Option<Cow<'static, str>> {
+        None
+    }
+
+    fn docs_url(&self) -> Option<Cow<'static, str>> {
+        None
+    }
+}
+
+impl<D: Serialize, C: BrestCode, Meta> Brest<D, C, Meta> {
+    /// A fail or error, depending on [`BrestCode::kind`], with the code's default message
+    /// and status.
+    pub fn from_code(code: C) -> Self {
+        let message = code.message();
+        Self::from_code_msg(code, message)
+    }
+
+    /// Like [`Brest::from_code`] with a custom message.
+    pub fn from_code_msg<M: ToString>(code: C, message: M) -> Self {
+        #[cfg(feature = "axum")]
+        {
+            let status =
+                StatusCode::from_u16(code.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            match code.kind() {
+                CodeKind::Fail => Self::fail_code_status(message, code, status),
+                CodeKind::Error => Self::error_code_status(message, code, status),
+            }
+        }
+        #[cfg(not(feature = "axum"))]
+        match code.kind() {
+            CodeKind::Fail => Self::fail_code(message, code),
+            CodeKind::Error => Self::error_code(message, code),
+        }
+    }
+}
+
+/// A code value as it was received, kept when it doesn't match any known code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RawCode {
+    Unsigned(u64),
+    Signed(i64),
+    String(String),
+    /// Any other JSON value, such as a float, a bool or an object.
+    Other(serde_json::Value),
+}
+
+impl Hash for RawCode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Unsigned(n) => n.hash(state),
+            Self::Signed(n) => n.hash(state),
+            Self::String(s) => s.hash(state),
+            // `Value` isn't `Hash`, equal values only need to share the discriminant's hash.
+            Self::Other(_) => {}
+        }
+    }
+}
+
+/// A code that deserializes values it doesn't know into [`UnknownOr::Unknown`] instead of
+/// failing, so clients keep working when servers add codes.
+///
+/// Use it as the `C` of a client side `Brest`, e.g. `Brest<User, UnknownOr<Code>>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnknownOr<C> {
+    Known(C),
+    Unknown(RawCode),
+}
+
+impl<C> UnknownOr<C> {
+    pub fn known(&self) -> Option<&C> {
+        match self {
+            Self::Known(code) => Some(code),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    pub fn into_known(self) -> Option<C> {
+        match self {
+            Self::Known(code) => Some(code),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl<C> From<C> for UnknownOr<C> {
+    fn from(code: C) -> Self {
+        Self::Known(code)
+    }
+}
+
+impl<C: Serialize> Serialize for UnknownOr<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Known(code) => code.serialize(serializer),
+            Self::Unknown(raw) => raw.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, C: Deserialize<'de>> Deserialize<'de> for UnknownOr<C> {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        type E = serde::de::value::Error;
+
+        let raw = RawCode::deserialize(deserializer)?;
+        let known = match &raw {
+            RawCode::Unsigned(n) => C::deserialize(IntoDeserializer::<E>::into_deserializer(*n)),
+            RawCode::Signed(n) => C::deserialize(IntoDeserializer::<E>::into_deserializer(*n)),
+            RawCode::String(s) => {
+                C::deserialize(IntoDeserializer::<E>::into_deserializer(s.clone()))
+            }
+            RawCode::Other(value) => C::deserialize(value.clone()).map_err(serde::de::Error::custom),
+        };
+        Ok(match known {
+            Ok(code) => Self::Known(code),
+            Err(_) => Self::Unknown(raw),
+        })
+    }
+}
+
+pub use catalog::{Catalog, CodeInfo};
+
+mod catalog {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+    use serde_json::Value;
+
+    use super::{BrestCode, CodeKind};
+
+    /// One catalog entry: a code as it appears on the wire plus its documentation.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct CodeInfo {
+        pub code: Value,
+        pub kind: CodeKind,
+        pub status: u16,
+        pub message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub docs_url: Option<String>,
+    }
+
+    impl CodeInfo {
+        pub fn of<C: BrestCode + Serialize>(code: &C) -> Self {
+            Self {
+                code: serde_json::to_value(code).unwrap_or(Value::Null),
+                kind: code.kind(),
+                status: code.status(),
+                message: code.message().into_owned(),
+                description: code.description().map(|d| d.into_owned()),
+                docs_url: code.docs_url().map(|u| u.into_owned()),
+            }
+        }
+    }
+
+    /// The codes of one or more [`BrestCode`] types, checked for uniqueness and rendered as
+    /// documentation. With `axum`, `Catalog::handler` serves them.
+    #[derive(Debug, Clone, Default)]
+    pub struct Catalog {
+        entries: Vec<CodeInfo>,
+    }
+
+    impl Catalog {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Adds every code of `C`.
+        #[must_use]
+        pub fn register<C: BrestCode + Serialize>(mut self) -> Self {
+            self.entries.extend(C::all().iter().map(CodeInfo::of));
+            self
+        }
+
+        pub fn entries(&self) -> &[CodeInfo] {
+            &self.entries
+        }
+
+        /// Codes that serialize to the same wire value more than once.
+        pub fn duplicates(&self) -> Vec<&Value> {
+            let mut seen = BTreeMap::new();
+            for entry in &self.entries {
+                *seen.entry(entry.code.to_string()).or_insert(0) += 1;
+            }
+            let mut duplicates: Vec<&Value> = Vec::new();
+            for entry in &self.entries {
+                if seen[&entry.code.to_string()] > 1 && !duplicates.contains(&&entry.code) {
+                    duplicates.push(&entry.code);
+                }
+            }
+            duplicates
+        }
+
+        /// Panics if a code is registered twice. Meant to be called at startup.
+        #[must_use]
+        pub fn assert_unique(self) -> Self {
+            let duplicates = self.duplicates();
+            if !duplicates.is_empty() {
+                let list: Vec<String> = duplicates.iter().map(|code| code.to_string()).collect();
+                panic!("duplicate error codes: {}", list.join(", "));
+            }
+            self
+        }
+
+        /// Renders the catalog as a Markdown table.
+        pub fn to_markdown(&self) -> String {
+            let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+            let mut out = String::from(
+                "| Code | Kind | Status | Message | Description |\n| --- | --- | --- | --- | --- |\n",
+            );
+            for entry in &self.entries {
+                let code = match &entry.code {
+                    Value::String(code) => code.clone(),
+                    code => code.to_string(),
+                };
+                let code = match &entry.docs_url {
+                    Some(url) => format!("[`{}`]({})", code, url),
+                    None => format!("`{}`", code),
+                };
+                let kind = match entry.kind {
+                    CodeKind::Fail => "fail",
+                    CodeKind::Error => "error",
+                };
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    code,
+                    kind,
+                    entry.status,
+                    cell(&entry.message),
+                    cell(entry.description.as_deref().unwrap_or("")),
+                ));
+            }
+            out
+        }
+    }
+
+    #[cfg(feature = "axum")]
+    mod handler {
+        use std::sync::Arc;
+
+        use axum::http::{header, HeaderMap};
+        use axum::response::{IntoResponse, Response};
+        use axum::routing::{get, MethodRouter};
+
+        use super::Catalog;
+        use crate::Brest;
+
+        impl Catalog {
+            /// A `GET` handler serving the catalog as a success envelope, or as Markdown when
+            /// the request accepts `text/markdown`.
+            pub fn handler<S>(self) -> MethodRouter<S>
+            where
+                S: Clone + Send + Sync + 'static,
+            {
+                let catalog = Arc::new(self);
+                get(move |headers: HeaderMap| async move { catalog.respond(&headers) })
+            }
+
+            fn respond(&self, headers: &HeaderMap) -> Response {
+                let markdown = headers
+                    .get(header::ACCEPT)
+                    .and_then(|accept| accept.to_str().ok())
+                    .is_some_and(|accept| accept.contains("text/markdown"));
+                if markdown {
+                    (
+                        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+                        self.to_markdown(),
+                    )
+                        .into_response()
+                } else {
+                    Brest::<_>::success(&self.entries).into_response()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Code {
+        NotFound,
+        Database,
+    }
+
+    impl BrestCode for Code {
+        fn all() -> Vec<Self> {
+            vec![Code::NotFound, Code::Database]
+        }
+
+        fn message(&self) -> Cow<'static, str> {
+            match self {
+                Code::NotFound => "Not found".into(),
+                Code::Database => "Database unavailable".into(),
+            }
+        }
+
+        fn status(&self) -> u16 {
+            match self {
+                Code::NotFound => 404,
+                Code::Database => 503,
+            }
+        }
+
+        fn docs_url(&self) -> Option<Cow<'static, str>> {
+            match self {
+                Code::NotFound => Some("https://example.com/codes/not_found".into()),
+                Code::Database => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_kind() {
+        assert_eq!(Code::NotFound.kind(), CodeKind::Fail);
+        assert_eq!(Code::Database.kind(), CodeKind::Error);
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum ClientCode {
+        NotFound,
+    }
+
+    #[test]
+    fn test_unknown_or() {
+        let brest: Brest<(), UnknownOr<ClientCode>> =
+            serde_json::from_str(r#"{"type":"fail","message":"x","code":"not_found"}"#).unwrap();
+        assert_eq!(brest.code(), Some(&UnknownOr::Known(ClientCode::NotFound)));
+
+        let brest: Brest<(), UnknownOr<ClientCode>> =
+            serde_json::from_str(r#"{"type":"fail","message":"x","code":"rate_limited"}"#).unwrap();
+        let code = brest.code().unwrap();
+        assert!(code.is_unknown());
+        assert_eq!(code, &UnknownOr::Unknown(RawCode::String("rate_limited".to_string())));
+
+        let code: UnknownOr<u8> = serde_json::from_str("300").unwrap();
+        assert_eq!(code, UnknownOr::Unknown(RawCode::Unsigned(300)));
+        assert_eq!(serde_json::to_string(&code).unwrap(), "300");
+
+        let code: UnknownOr<u8> = serde_json::from_str("7").unwrap();
+        assert_eq!(code.known(), Some(&7));
+        assert_eq!(serde_json::from_str::<UnknownOr<u8>>("-1").unwrap(), UnknownOr::Unknown(RawCode::Signed(-1)));
+
+        for raw in ["1.5", "true", r#"{"id":7}"#, "[1]"] {
+            let body = format!(r#"{{"type":"fail","message":"x","code":{}}}"#, raw);
+            let brest: Brest<(), UnknownOr<ClientCode>> = serde_json::from_str(&body).unwrap();
+            let expected: serde_json::Value = serde_json::from_str(raw).unwrap();
+            assert_eq!(brest.code(), Some(&UnknownOr::Unknown(RawCode::Other(expected.clone()))));
+            assert_eq!(serde_json::to_string(&RawCode::Other(expected)).unwrap(), raw);
+        }
+        let code: UnknownOr<f64> = serde_json::from_str("1.5").unwrap();
+        assert_eq!(code.known(), Some(&1.5));
+    }
+
+    #[test]
+    fn test_from_code() {
+        let brest = Brest::<(), Code>::from_code(Code::NotFound);
+        assert!(brest.is_fail());
+        assert_eq!(brest.message(), Some("Not found"));
+        assert_eq!(brest.code(), Some(&Code::NotFound));
+
+        let brest = Brest::<(), Code>::from_code_msg(Code::Database, "Replica lagging");
+        assert!(brest.is_error());
+        assert_eq!(brest.message(), Some("Replica lagging"));
+
+        #[cfg(feature = "axum")]
+        {
+            assert_eq!(Brest::<(), Code>::from_code(Code::NotFound).status(), StatusCode::NOT_FOUND);
+            assert_eq!(brest.status(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Legacy {
+        NotFound,
+    }
+
+    impl BrestCode for Legacy {
+        fn all() -> Vec<Self> {
+            vec![Legacy::NotFound]
+        }
+
+        fn message(&self) -> Cow<'static, str> {
+            "Gone".into()
+        }
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let catalog = Catalog::new().register::<Code>();
+        assert!(catalog.duplicates().is_empty());
+        let catalog = catalog.assert_unique().register::<Legacy>();
+        assert_eq!(catalog.duplicates(), [&serde_json::json!("not_found")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate error codes: \"not_found\"")]
+    fn test_assert_unique() {
+        let _ = Catalog::new().register::<Code>().register::<Legacy>().assert_unique();
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = Catalog::new().register::<Code>().to_markdown();
+        assert_eq!(
+            markdown,
+            "| Code | Kind | Status | Message | Description |\n\
+             | --- | --- | --- | --- | --- |\n\
+             | [`not_found`](https://example.com/codes/not_found) | fail | 404 | Not found |  |\n\
+             | `database` | error | 503 | Database unavailable |  |\n"
+        );
+    }
+
+    #[cfg(feature = "axum")]
+    mod axum_tests {
+        use super::*;
+        use axum::body::Body;
+        use axum::extract::Request;
+        use axum::http::header;
+        use axum::Router;
+        use tower::ServiceExt;
+
+        #[tokio::test]
+        async fn test_handler() {
+            let app: Router = Router::new().route("/codes", Catalog::new().register::<Code>().handler());
+
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri("/codes").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(
+                std::str::from_utf8(&bytes).unwrap(),
+                r#"{"type":"success","data":[{"code":"not_found","kind":"fail","status":404,"message":"Not found","docs_url":"https://example.com/codes/not_found"},{"code":"database","kind":"error","status":503,"message":"Database unavailable"}]}"#
+            );
+
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/codes")
+                        .header(header::ACCEPT, "text/markdown")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "text/markdown; charset=utf-8");
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Alternative deserialization modes for [`Brest`].
+//!
+//! Deserialize into [`Lenient`] to accept envelopes from legacy or third-party services, or
+//! into [`Strict`] to check that a payload follows the contract exactly. Both unwrap into a
+//! plain [`Brest`].
+
+use serde::de::value::{Error as ValueError, UnitDeserializer};
+use serde::de::{Error as _, IgnoredAny};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::debug::DebugInfo;
+use crate::Brest;
+
+/// Deserializes a [`Brest`] leniently:
+///
+/// - the variant name is matched in any case,
+/// - `status` is accepted in place of `type`,
+/// - `msg` and `error` are accepted in place of `message`,
+/// - `data` may be missing from a success, in which case it's deserialized from `null`.
+///
+/// Statuses default as with a plain deserialization.
+#[derive(Debug, PartialEq)]
+pub struct Lenient<D = (), C = u32, Meta = ()>(pub Brest<D, C, Meta>);
+
+/// Deserializes a [`Brest`] rejecting any key the envelope doesn't define.
+#[derive(Debug, PartialEq)]
+pub struct Strict<D = (), C = u32, Meta = ()>(pub Brest<D, C, Meta>);
+
+impl<D, C, Meta> Lenient<D, C, Meta> {
+    pub fn into_inner(self) -> Brest<D, C, Meta> {
+        self.0
+    }
+}
+
+impl<D, C, Meta> Strict<D, C, Meta> {
+    pub fn into_inner(self) -> Brest<D, C, Meta> {
+        self.0
+    }
+}
+
+impl<D, C, Meta> From<Lenient<D, C, Meta>> for Brest<D, C, Meta> {
+    fn from(value: Lenient<D, C, Meta>) -> Self {
+        value.0
+    }
+}
+
+impl<D, C, Meta> From<Strict<D, C, Meta>> for Brest<D, C, Meta> {
+    fn from(value: Strict<D, C, Meta>) -> Self {
+        value.0
+    }
+}
+
+/// A string, or anything else which is ignored.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Text {
+    Str(String),
+    Other(IgnoredAny),
+}
+
+impl Text {
+    fn into_string(self) -> Option<String> {
+        match self {
+            Text::Str(s) => Some(s),
+            Text::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LenientRaw<D, C, Meta> {
+    #[serde(rename = "type")]
+    kind: Option<Text>,
+    status: Option<Text>,
+    data: Option<D>,
+    message: Option<Text>,
+    msg: Option<Text>,
+    error: Option<Text>,
+    code: Option<C>,
+    debug: Option<DebugInfo>,
+    #[serde(default = "Option::default", deserialize_with = "crate::deserialize_meta")]
+    #[serde(bound(deserialize = "Meta: Deserialize<'de>"))]
+    meta: Option<Meta>,
+}
+
+impl<'de, D, C, Meta> Deserialize<'de> for Lenient<D, C, Meta>
+where
+    D: Serialize + Deserialize<'de>,
+    C: Deserialize<'de>,
+    Meta: Deserialize<'de>,
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let raw = LenientRaw::<D, C, Meta>::deserialize(deserializer)?;
+
+        let kind = raw
+            .kind
+            .and_then(Text::into_string)
+            .or_else(|| raw.status.and_then(Text::into_string))
+            .ok_or_else(|| De::Error::missing_field("type"))?
+            .to_lowercase();
+        let message = || {
+            [raw.message, raw.msg, raw.error]
+                .into_iter()
+                .flatten()
+                .find_map(Text::into_string)
+                .ok_or_else(|| De::Error::missing_field("message"))
+        };
+
+        let brest = match kind.as_str() {
+            "success" => {
+                let data = match raw.data {
+                    Some(data) => data,
+                    None => D::deserialize(UnitDeserializer::<ValueError>::new())
+                        .map_err(|_| De::Error::missing_field("data"))?,
+                };
+                Brest::success(data)
+            }
+            "error" => with_fields(Brest::error(message()?), raw.code, raw.debug),
+            "fail" => with_fields(Brest::fail(message()?), raw.code, raw.debug),
+            other => {
+                return Err(De::Error::unknown_variant(other, &["success", "error", "fail"]));
+            }
+        };
+
+        Ok(Lenient(match raw.meta {
+            Some(meta) => brest.with_meta(meta),
+            None => brest,
+        }))
+    }
+}
+
+fn with_fields<D: Serialize, C, Meta>(
+    brest: Brest<D, C, Meta>,
+    code: Option<C>,
+    debug: Option<DebugInfo>,
+) -> Brest<D, C, Meta> {
+    let brest = match code {
+        Some(code) => brest.with_code(code),
+        None => brest,
+    };
+    match debug {
+        Some(debug) => brest.with_debug(debug),
+        None => brest,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictDebug {
+    #[serde(default)]
+    causes: Vec<String>,
+    backtrace: Option<String>,
+}
+
+impl From<StrictDebug> for DebugInfo {
+    fn from(value: StrictDebug) -> Self {
+        DebugInfo {
+            causes: value.causes,
+            backtrace: value.backtrace,
+        }
+    }
+}
+
+// With `deserialize_with`, serde no longer treats a missing `Option` as `None`.
+fn required<'de, De, T>(deserializer: De) -> Result<T, De::Error>
+where
+    De: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer)
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+#[serde(bound(deserialize = "D: Deserialize<'de>, C: Deserialize<'de>, Meta: Deserialize<'de>"))]
+enum StrictRaw<D, C, Meta> {
+    Success {
+        #[serde(deserialize_with = "required")]
+        data: D,
+        meta: Option<Meta>,
+    },
+    Error {
+        message: String,
+        code: Option<C>,
+        debug: Option<StrictDebug>,
+        meta: Option<Meta>,
+    },
+    Fail {
+        message: String,
+        code: Option<C>,
+        debug: Option<StrictDebug>,
+        meta: Option<Meta>,
+    },
+}
+
+impl<'de, D, C, Meta> Deserialize<'de> for Strict<D, C, Meta>
+where
+    D: Serialize + Deserialize<'de>,
+    C: Deserialize<'de>,
+    Meta: Deserialize<'de>,
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let (brest, meta) = match StrictRaw::<D, C, Meta>::deserialize(deserializer)? {
+            StrictRaw::Success { data, meta } => (Brest::success(data), meta),
+            StrictRaw::Error { message, code, debug, meta } => (
+                with_fields(Brest::error(message), code, debug.map(Into::into)),
+                meta,
+            ),
+            StrictRaw::Fail { message, code, debug, meta } => (
+                with_fields(Brest::fail(message), code, debug.map(Into::into)),
+                meta,
+            ),
+        };
+
+        Ok(Strict(match meta {
+            Some(meta) => brest.with_meta(meta),
+            None => brest,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lenient(json: &str) -> Brest<Option<u32>, u32> {
+        serde_json::from_str::<Lenient<Option<u32>, u32>>(json).unwrap().into_inner()
+    }
+
+    fn strict(json: &str) -> Result<Brest<Option<u32>, u32>, serde_json::Error> {
+        serde_json::from_str::<Strict<Option<u32>, u32>>(json).map(Strict::into_inner)
+    }
+
+    #[test]
+    fn test_lenient() {
+        assert!(lenient(r#"{"type":"SUCCESS","data":1}"#).is_success_and(|data| data == Some(1)));
+        assert!(lenient(r#"{"status":"success"}"#).is_success_and(|data| data.is_none()));
+
+        let brest = lenient(r#"{"status":"Fail","msg":"Nope","code":3,"extra":true}"#);
+        assert!(brest.is_fail());
+        assert_eq!(brest.message(), Some("Nope"));
+        assert_eq!(brest.code(), Some(&3));
+
+        let brest = lenient(r#"{"type":"error","status":500,"error":"Boom"}"#);
+        assert!(brest.is_error());
+        assert_eq!(brest.message(), Some("Boom"));
+
+        let brest = serde_json::from_str::<Lenient>(r#"{"type":"fail","msg":"x","meta":{"a":1}}"#);
+        assert_eq!(brest.unwrap().into_inner().meta(), None);
+
+        assert!(serde_json::from_str::<Lenient>(r#"{"type":"weird","message":"x"}"#).is_err());
+        assert!(serde_json::from_str::<Lenient>(r#"{"type":"fail"}"#).is_err());
+        assert!(serde_json::from_str::<Lenient<Vec<u32>>>(r#"{"type":"success"}"#).is_err());
+    }
+
+    #[test]
+    fn test_strict() {
+        assert!(strict(r#"{"type":"success","data":1,"meta":null}"#).is_ok());
+        assert!(strict(r#"{"type":"fail","message":"x","code":1,"debug":{"causes":["y"]}}"#).is_ok());
+
+        assert!(strict(r#"{"type":"success","data":1,"extra":true}"#).is_err());
+        assert!(strict(r#"{"type":"fail","message":"x","msg":"x"}"#).is_err());
+        assert!(strict(r#"{"type":"error","message":"x","debug":{"cause":"y"}}"#).is_err());
+        assert!(strict(r#"{"type":"Success","data":1}"#).is_err());
+        assert!(strict(r#"{"type":"success"}"#).is_err());
+    }
+
+    #[test]
+    fn test_strict_accepts_serialized() {
+        let debug = DebugInfo {
+            causes: vec!["cause".to_string()],
+            backtrace: Some("trace".to_string()),
+        };
+        let samples: Vec<Brest<Option<u32>, u32, u32>> = vec![
+            Brest::success(Some(1)).with_meta(2),
+            Brest::error_code("x", 1).with_debug(debug.clone()).with_meta(2),
+            Brest::fail_code("x", 1).with_debug(debug).with_meta(2),
+        ];
+        for brest in samples {
+            let json = serde_json::to_string(&brest).unwrap();
+            let parsed = serde_json::from_str::<Strict<Option<u32>, u32, u32>>(&json).unwrap();
+            assert_eq!(serde_json::to_string(&parsed.0).unwrap(), json);
+        }
+    }
+}
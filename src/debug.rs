@@ -0,0 +1,101 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// Controls how much diagnostic information is attached to error and fail envelopes
+/// built from a [`std::error::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugMode {
+    /// No `debug` object is emitted.
+    Off,
+    /// The `source()` chain of the error is emitted as `debug.causes`.
+    Causes,
+    /// Like [`DebugMode::Causes`], plus a captured backtrace as `debug.backtrace`.
+    Backtrace,
+}
+
+const UNSET: u8 = 0;
+const OFF: u8 = 1;
+const CAUSES: u8 = 2;
+const BACKTRACE: u8 = 3;
+
+static MODE: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Sets the process wide [`DebugMode`].
+pub fn set_mode(mode: DebugMode) {
+    let value = match mode {
+        DebugMode::Off => OFF,
+        DebugMode::Causes => CAUSES,
+        DebugMode::Backtrace => BACKTRACE,
+    };
+    MODE.store(value, Ordering::Relaxed);
+}
+
+/// Returns the current [`DebugMode`].
+///
+/// Unless overridden with [`set_mode`], this is [`DebugMode::Causes`] in debug builds
+/// and [`DebugMode::Off`] in release builds.
+pub fn mode() -> DebugMode {
+    match MODE.load(Ordering::Relaxed) {
+        OFF => DebugMode::Off,
+        CAUSES => DebugMode::Causes,
+        BACKTRACE => DebugMode::Backtrace,
+        _ if cfg!(debug_assertions) => DebugMode::Causes,
+        _ => DebugMode::Off,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "ts", derive(TS))]
+#[derive(Serialize, Deserialize)]
+pub struct DebugInfo {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "ts", ts(as = "Option<Vec<String>>", optional))]
+    pub causes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional))]
+    pub backtrace: Option<String>,
+}
+
+impl DebugInfo {
+    /// Collects the cause chain of `error` according to the current [`mode`].
+    ///
+    /// Returns `None` when the mode is [`DebugMode::Off`].
+    pub fn capture<E: Error + ?Sized>(error: &E) -> Option<Self> {
+        let mode = mode();
+        if mode == DebugMode::Off {
+            return None;
+        }
+
+        let mut causes = Vec::new();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+
+        let backtrace = match mode {
+            DebugMode::Backtrace => {
+                let backtrace = Backtrace::force_capture();
+                match backtrace.status() {
+                    BacktraceStatus::Captured => Some(backtrace.to_string()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        Some(Self { causes, backtrace })
+    }
+}
@@ -1,11 +1,16 @@
 use std::ops::Deref;
+use std::time::SystemTime;
 
 use axum::extract::{FromRequest, Request};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderName, StatusCode, Uri};
 use axum::extract::rejection::{ExtensionRejection, FormRejection, JsonRejection, PathRejection, QueryRejection};
 use axum::response::IntoResponse;
 use serde::Serialize;
 
-use crate::Brest;
+use crate::{Brest, Paginated};
+
+pub use crate::middleware::RequestId;
 
 #[derive(FromRequest)]
 #[from_request(via(axum::Json), rejection(Brest))]
@@ -226,4 +231,445 @@ impl Deref for RawPathParams {
     fn deref(&self) -> &Self::Target {
         &self.0
     }
-}
\ No newline at end of file
+}
+
+/// Limits applied by the [`Pagination`] extractor.
+///
+/// Insert it as a request extension to override the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaginationConfig {
+    pub default_per_page: u64,
+    pub max_per_page: u64,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            default_per_page: 20,
+            max_per_page: 100,
+        }
+    }
+}
+
+/// Parses the `page`, `per_page` and `cursor` query parameters.
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    pub page: u64,
+    pub per_page: u64,
+    pub cursor: Option<String>,
+    uri: Uri,
+}
+
+impl Pagination {
+    pub fn offset(&self) -> u64 {
+        (self.page - 1).saturating_mul(self.per_page)
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.per_page
+    }
+
+    /// Builds an offset page with `next`/`prev` links pointing at the current request.
+    pub fn paginate<T>(&self, items: Vec<T>, total: Option<u64>) -> Paginated<T> {
+        let page = Paginated::offset(items, self.page, self.per_page, total);
+        // No link past the last representable page.
+        let next = self
+            .page
+            .checked_add(1)
+            .filter(|_| page.has_next())
+            .map(|next| self.link("page", &next.to_string()));
+        let prev = page
+            .has_prev()
+            .then(|| self.link("page", &(self.page - 1).to_string()));
+        page.with_links(next, prev)
+    }
+
+    /// Builds a cursor page with a `next` link pointing at the current request.
+    pub fn paginate_cursor<T>(&self, items: Vec<T>, next_cursor: Option<String>) -> Paginated<T> {
+        let next = next_cursor.as_deref().map(|cursor| self.link("cursor", cursor));
+        Paginated::cursor(items, next_cursor).with_links(next, None)
+    }
+
+    fn link(&self, key: &str, value: &str) -> String {
+        let mut params: Vec<(String, String)> =
+            serde_urlencoded::from_str(self.uri.query().unwrap_or_default()).unwrap_or_default();
+        params.retain(|(k, _)| k != key);
+        params.push((key.to_string(), value.to_string()));
+        if key == "page" && !params.iter().any(|(k, _)| k == "per_page") {
+            params.push(("per_page".to_string(), self.per_page.to_string()));
+        }
+        let query = serde_urlencoded::to_string(&params).unwrap_or_default();
+        format!("{}?{}", self.uri.path(), query)
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("Invalid `{}`: expected a positive integer", name)),
+    }
+}
+
+impl<S> axum::extract::FromRequestParts<S> for Pagination
+where
+    S: Send + Sync,
+{
+    type Rejection = Brest;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<PaginationConfig>()
+            .copied()
+            .unwrap_or_default();
+        let axum::extract::Query(params) =
+            axum::extract::Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)?;
+
+        let mut page = 1;
+        let mut per_page = config.default_per_page;
+        let mut cursor = None;
+        for (key, value) in params {
+            match key.as_str() {
+                "page" => page = parse_positive("page", &value).map_err(Brest::fail)?,
+                "per_page" => per_page = parse_positive("per_page", &value).map_err(Brest::fail)?,
+                "cursor" => cursor = Some(value),
+                _ => {}
+            }
+        }
+
+        if per_page > config.max_per_page {
+            return Err(Brest::fail(format!(
+                "Invalid `per_page`: must not exceed {}",
+                config.max_per_page
+            )));
+        }
+
+        Ok(Pagination {
+            page,
+            per_page,
+            cursor,
+            uri: parts.uri.clone(),
+        })
+    }
+}
+
+/// Code of the fail returned when a precondition header doesn't match.
+pub const PRECONDITION_FAILED: u32 = 412;
+/// Code of the fail returned when a required precondition header is missing.
+pub const PRECONDITION_REQUIRED: u32 = 428;
+
+fn precondition_failed() -> Brest {
+    Brest::fail_code_status(
+        "Precondition failed",
+        PRECONDITION_FAILED,
+        StatusCode::PRECONDITION_FAILED,
+    )
+}
+
+fn precondition_required(header: &HeaderName) -> Brest {
+    Brest::fail_code_status(
+        format!("Missing `{}` header", header),
+        PRECONDITION_REQUIRED,
+        StatusCode::PRECONDITION_REQUIRED,
+    )
+}
+
+/// The `name` header with its lines joined by `, `, as list headers may be split across lines.
+fn header_str(parts: &Parts, name: &HeaderName) -> Result<Option<String>, Brest> {
+    let values = parts
+        .headers
+        .get_all(name)
+        .iter()
+        .map(|value| value.to_str())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Brest::fail(format!("Invalid `{}` header", name)))?;
+    Ok((!values.is_empty()).then(|| values.join(", ")))
+}
+
+/// The parsed `If-Match` header.
+///
+/// Rejects with 428 Precondition Required when the header is missing, use `Option<IfMatch>`
+/// to make it optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    Any,
+    Tags(Vec<String>),
+}
+
+impl IfMatch {
+    fn parse(value: &str) -> Result<Self, Brest> {
+        if value.trim() == "*" {
+            return Ok(Self::Any);
+        }
+
+        let tags = value
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| {
+                let opaque = tag.strip_prefix("W/").unwrap_or(tag);
+                if opaque.len() >= 2 && opaque.starts_with('"') && opaque.ends_with('"') {
+                    Ok(tag.to_string())
+                } else {
+                    Err(Brest::fail("Invalid `if-match` header"))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::Tags(tags))
+    }
+
+    /// Strong comparison against the current `etag` of the resource, quoted or not.
+    /// Weak tags never match.
+    pub fn matches(&self, etag: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Tags(tags) => {
+                let etag = etag.trim();
+                if etag.starts_with("W/") {
+                    return false;
+                }
+                let quoted;
+                let etag = if etag.starts_with('"') {
+                    etag
+                } else {
+                    quoted = format!("\"{}\"", etag);
+                    &quoted
+                };
+                tags.iter().any(|tag| tag == etag)
+            }
+        }
+    }
+
+    /// Rejects with 412 Precondition Failed unless [`IfMatch::matches`] the current `etag`.
+    pub fn check(&self, etag: &str) -> Result<(), Brest> {
+        if self.matches(etag) {
+            Ok(())
+        } else {
+            Err(precondition_failed())
+        }
+    }
+}
+
+impl<S> axum::extract::FromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = Brest;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match header_str(parts, &header::IF_MATCH)? {
+            Some(value) => IfMatch::parse(&value),
+            None => Err(precondition_required(&header::IF_MATCH)),
+        }
+    }
+}
+
+impl<S> axum::extract::OptionalFromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = Brest;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        header_str(parts, &header::IF_MATCH)?
+            .as_deref()
+            .map(IfMatch::parse)
+            .transpose()
+    }
+}
+
+/// The parsed `If-Unmodified-Since` header.
+///
+/// Rejects with 428 Precondition Required when the header is missing, use
+/// `Option<IfUnmodifiedSince>` to make it optional.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IfUnmodifiedSince(pub SystemTime);
+
+impl IfUnmodifiedSince {
+    fn parse(value: &str) -> Result<Self, Brest> {
+        httpdate::parse_http_date(value)
+            .map(IfUnmodifiedSince)
+            .map_err(|_| Brest::fail("Invalid `if-unmodified-since` header"))
+    }
+
+    /// Whether a resource last modified at `last_modified` satisfies the precondition,
+    /// compared at the one second precision of HTTP dates.
+    pub fn matches(&self, last_modified: SystemTime) -> bool {
+        let secs = |time: SystemTime| {
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        };
+        secs(last_modified) <= secs(self.0)
+    }
+
+    /// Rejects with 412 Precondition Failed if the resource was modified since.
+    pub fn check(&self, last_modified: SystemTime) -> Result<(), Brest> {
+        if self.matches(last_modified) {
+            Ok(())
+        } else {
+            Err(precondition_failed())
+        }
+    }
+}
+
+impl<S> axum::extract::FromRequestParts<S> for IfUnmodifiedSince
+where
+    S: Send + Sync,
+{
+    type Rejection = Brest;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match header_str(parts, &header::IF_UNMODIFIED_SINCE)? {
+            Some(value) => IfUnmodifiedSince::parse(&value),
+            None => Err(precondition_required(&header::IF_UNMODIFIED_SINCE)),
+        }
+    }
+}
+
+impl<S> axum::extract::OptionalFromRequestParts<S> for IfUnmodifiedSince
+where
+    S: Send + Sync,
+{
+    type Rejection = Brest;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        header_str(parts, &header::IF_UNMODIFIED_SINCE)?
+            .as_deref()
+            .map(IfUnmodifiedSince::parse)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{FromRequestParts, OptionalFromRequestParts};
+    use std::time::Duration;
+
+    async fn pagination(uri: &str, config: Option<PaginationConfig>) -> Result<Pagination, Brest> {
+        let mut req = Request::builder().uri(uri);
+        if let Some(config) = config {
+            req = req.extension(config);
+        }
+        let (mut parts, _) = req.body(()).unwrap().into_parts();
+        Pagination::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_pagination_defaults() {
+        let p = pagination("/items", None).await.unwrap();
+        assert_eq!((p.page, p.per_page, p.cursor), (1, 20, None));
+    }
+
+    #[tokio::test]
+    async fn test_pagination_parses_query() {
+        let p = pagination("/items?page=3&per_page=10&cursor=abc", None).await.unwrap();
+        assert_eq!((p.page, p.per_page, p.cursor.as_deref()), (3, 10, Some("abc")));
+        assert_eq!(p.offset(), 20);
+        assert_eq!(p.limit(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_rejects_invalid() {
+        let err = pagination("/items?page=0", None).await.unwrap_err();
+        assert!(err.is_fail());
+
+        let err = pagination("/items?per_page=abc", None).await.unwrap_err();
+        assert!(err.is_fail_and(|f| f.status == StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_pagination_max_per_page() {
+        let config = PaginationConfig {
+            default_per_page: 5,
+            max_per_page: 10,
+        };
+        assert!(pagination("/items?per_page=11", Some(config)).await.is_err());
+        assert_eq!(pagination("/items", Some(config)).await.unwrap().per_page, 5);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_links() {
+        let p = pagination("/items?page=2&per_page=2&q=x", None).await.unwrap();
+        let page = p.paginate(vec![3, 4], Some(5));
+        assert_eq!(page.next.as_deref(), Some("/items?per_page=2&q=x&page=3"));
+        assert_eq!(page.prev.as_deref(), Some("/items?per_page=2&q=x&page=1"));
+
+        let page = p.paginate_cursor(vec![3, 4], Some("n".to_string()));
+        assert_eq!(page.next.as_deref(), Some("/items?page=2&per_page=2&q=x&cursor=n"));
+
+        let p = pagination(&format!("/items?page={}&per_page=1", u64::MAX), None).await.unwrap();
+        let page = p.paginate(vec![1], None);
+        assert!(page.has_next());
+        assert_eq!(page.next, None);
+    }
+
+    fn parts(headers: &[(HeaderName, &str)]) -> Parts {
+        let mut req = Request::builder();
+        for (name, value) in headers {
+            req = req.header(name, *value);
+        }
+        req.body(()).unwrap().into_parts().0
+    }
+
+    #[tokio::test]
+    async fn test_if_match() {
+        let mut p = parts(&[(header::IF_MATCH, r#""a", W/"b""#)]);
+        let if_match = <IfMatch as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap();
+        assert_eq!(if_match, IfMatch::Tags(vec![r#""a""#.to_string(), r#"W/"b""#.to_string()]));
+        assert!(if_match.matches("a"));
+        assert!(if_match.matches(r#""a""#));
+        assert!(!if_match.matches("b"));
+        assert!(if_match.check("c").unwrap_err().is_fail_and(|f| {
+            f.code == Some(PRECONDITION_FAILED) && f.status == StatusCode::PRECONDITION_FAILED
+        }));
+
+        let mut p = parts(&[(header::IF_MATCH, "*")]);
+        let if_match = <IfMatch as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap();
+        assert!(if_match.check("anything").is_ok());
+
+        let mut p = parts(&[(header::IF_MATCH, r#""a""#), (header::IF_MATCH, r#""b""#)]);
+        let if_match = <IfMatch as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap();
+        assert!(if_match.matches("b"));
+    }
+
+    #[tokio::test]
+    async fn test_if_match_missing_or_invalid() {
+        let mut p = parts(&[]);
+        let err = <IfMatch as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap_err();
+        assert!(err.is_fail_and(|f| {
+            f.code == Some(PRECONDITION_REQUIRED) && f.status == StatusCode::PRECONDITION_REQUIRED
+        }));
+
+        let opt = <IfMatch as OptionalFromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap();
+        assert_eq!(opt, None);
+
+        let mut p = parts(&[(header::IF_MATCH, "unquoted")]);
+        let err = <IfMatch as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap_err();
+        assert!(err.is_fail_and(|f| f.status == StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_if_unmodified_since() {
+        let since = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        let mut p = parts(&[(header::IF_UNMODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        let header = <IfUnmodifiedSince as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap();
+        assert_eq!(header, IfUnmodifiedSince(since));
+        assert!(header.check(since + Duration::from_millis(500)).is_ok());
+        assert!(header.check(since + Duration::from_secs(1)).is_err());
+
+        let mut p = parts(&[]);
+        let err = <IfUnmodifiedSince as FromRequestParts<()>>::from_request_parts(&mut p, &()).await.unwrap_err();
+        assert!(err.is_fail_and(|f| f.status == StatusCode::PRECONDITION_REQUIRED));
+    }
+}
@@ -4,73 +4,170 @@
 use std::ops::{ControlFlow, FromResidual, Try};
 
 #[cfg(feature = "axum")]
-use axum::{http::StatusCode, response::IntoResponse};
-
-#[cfg(feature = "schemars")]
-use schemars::JsonSchema;
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
 
 #[cfg(feature = "axum")]
 use serde::{Serializer, ser::SerializeStruct as _};
 
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
 use serde::{Deserialize, Serialize};
 
 use std::fmt::Debug;
 
+#[cfg(feature = "aide")]
+pub mod aide;
+pub mod code;
+pub mod de;
+pub mod debug;
 #[cfg(feature = "axum")]
 pub mod extractors;
+#[cfg(feature = "axum")]
+pub mod middleware;
+#[cfg(feature = "utoipa")]
+pub mod openapi;
+pub mod pagination;
+#[cfg(feature = "axum")]
+pub mod router;
+#[cfg(feature = "schemars")]
+mod schema;
+#[cfg(feature = "ts")]
+pub mod ts;
+
+pub use code::{BrestCode, UnknownOr};
+use debug::DebugInfo;
+pub use pagination::Paginated;
 
 #[derive(Debug)]
-#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "ts", derive(TS), ts(bound = "D: TS, C: TS, Meta: TS"))]
 #[derive(Serialize, Deserialize, PartialEq)]
 #[serde(
     rename_all = "lowercase",
     tag = "type"
 )]
-pub enum Brest<D: Serialize = (), C = u32> {
+pub enum Brest<D = (), C = u32, Meta = ()> {
     Success {
         data: D,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(default = "Option::default", deserialize_with = "deserialize_meta")]
+        #[serde(bound(deserialize = "Meta: Deserialize<'de>"))]
+        #[cfg_attr(feature = "ts", ts(optional))]
+        meta: Option<Meta>,
         #[cfg(feature = "axum")]
-        #[serde(skip)]
+        #[serde(skip, default = "default_success_status")]
         status: StatusCode,
+        #[cfg(feature = "axum")]
+        #[serde(skip)]
+        headers: Option<Box<HeaderMap>>,
     },
     Error {
         message: String,
         #[serde(skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts", ts(optional))]
         code: Option<C>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts", ts(optional))]
+        debug: Option<DebugInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(default = "Option::default", deserialize_with = "deserialize_meta")]
+        #[serde(bound(deserialize = "Meta: Deserialize<'de>"))]
+        #[cfg_attr(feature = "ts", ts(optional))]
+        meta: Option<Meta>,
         #[cfg(feature = "axum")]
-        #[serde(skip)]
+        #[serde(skip, default = "default_error_status")]
         status: StatusCode,
+        #[cfg(feature = "axum")]
+        #[serde(skip)]
+        headers: Option<Box<HeaderMap>>,
     },
     Fail {
         message: String,
         #[serde(skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts", ts(optional))]
         code: Option<C>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts", ts(optional))]
+        debug: Option<DebugInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(default = "Option::default", deserialize_with = "deserialize_meta")]
+        #[serde(bound(deserialize = "Meta: Deserialize<'de>"))]
+        #[cfg_attr(feature = "ts", ts(optional))]
+        meta: Option<Meta>,
         #[cfg(feature = "axum")]
-        #[serde(skip)]
+        #[serde(skip, default = "default_fail_status")]
         status: StatusCode,
+        #[cfg(feature = "axum")]
+        #[serde(skip)]
+        headers: Option<Box<HeaderMap>>,
     },
 }
 
-impl<D: Serialize, C> Brest<D, C> {
+/// Middleware may add to `meta` regardless of the type the client expects. A zero-sized `Meta`,
+/// such as the default `()`, can't hold any of it, so whatever was sent is ignored.
+pub(crate) fn deserialize_meta<'de, De, Meta>(deserializer: De) -> Result<Option<Meta>, De::Error>
+where
+    De: serde::Deserializer<'de>,
+    Meta: Deserialize<'de>,
+{
+    if std::mem::size_of::<Meta>() == 0 {
+        serde::de::IgnoredAny::deserialize(deserializer)?;
+        return Ok(None);
+    }
+    Option::<Meta>::deserialize(deserializer)
+}
+
+// Deserialized envelopes carry no status, these stand in for the one they were sent with.
+#[cfg(feature = "axum")]
+fn default_success_status() -> StatusCode {
+    StatusCode::OK
+}
+
+#[cfg(feature = "axum")]
+fn default_error_status() -> StatusCode {
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[cfg(feature = "axum")]
+fn default_fail_status() -> StatusCode {
+    StatusCode::BAD_REQUEST
+}
+
+impl<D: Serialize, C, Meta> Brest<D, C, Meta> {
     pub fn success(data: D) -> Self {
         Self::Success {
             data,
+            meta: None,
             #[cfg(feature = "axum")]
             status: StatusCode::OK,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 
     #[cfg(feature = "axum")]
     pub fn success_status(data: D, status: StatusCode) -> Self {
-        Self::Success { data, status }
+        Self::Success {
+            data,
+            meta: None,
+            status,
+            headers: None,
+        }
     }
 
     pub fn error<M: ToString>(message: M) -> Self {
         Self::Error {
             message: message.to_string(),
             code: None,
+            debug: None,
+            meta: None,
             #[cfg(feature = "axum")]
             status: StatusCode::INTERNAL_SERVER_ERROR,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 
@@ -78,8 +175,12 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Error {
             message: message.to_string(),
             code: Some(code),
+            debug: None,
+            meta: None,
             #[cfg(feature = "axum")]
             status: StatusCode::INTERNAL_SERVER_ERROR,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 
@@ -88,8 +189,12 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Error {
             message: message.to_string(),
             code: None,
+            debug: None,
+            meta: None,
             #[cfg(feature = "axum")]
             status,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 
@@ -98,7 +203,10 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Error {
             message: message.to_string(),
             code: Some(code),
+            debug: None,
+            meta: None,
             status,
+            headers: None,
         }
     }
 
@@ -106,8 +214,12 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Fail {
             message: message.to_string(),
             code: None,
+            debug: None,
+            meta: None,
             #[cfg(feature = "axum")]
             status: StatusCode::BAD_REQUEST,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 
@@ -115,8 +227,12 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Fail {
             message: message.to_string(),
             code: Some(code),
+            debug: None,
+            meta: None,
             #[cfg(feature = "axum")]
             status: StatusCode::BAD_REQUEST,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 
@@ -125,7 +241,10 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Fail {
             message: message.to_string(),
             code: None,
+            debug: None,
+            meta: None,
             status,
+            headers: None,
         }
     }
 
@@ -134,13 +253,148 @@ impl<D: Serialize, C> Brest<D, C> {
         Self::Fail {
             message: message.to_string(),
             code: Some(code),
+            debug: None,
+            meta: None,
             #[cfg(feature = "axum")]
             status,
+            #[cfg(feature = "axum")]
+            headers: None,
+        }
+    }
+
+    /// Builds an error from `error`, attaching its cause chain as `debug` when
+    /// [`debug::mode`] allows it.
+    pub fn error_report<E: std::error::Error + ?Sized>(error: &E) -> Self {
+        Self::Error {
+            message: error.to_string(),
+            code: None,
+            debug: DebugInfo::capture(error),
+            meta: None,
+            #[cfg(feature = "axum")]
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            #[cfg(feature = "axum")]
+            headers: None,
+        }
+    }
+
+    /// Builds a fail from `error`, attaching its cause chain as `debug` when
+    /// [`debug::mode`] allows it.
+    pub fn fail_report<E: std::error::Error + ?Sized>(error: &E) -> Self {
+        Self::Fail {
+            message: error.to_string(),
+            code: None,
+            debug: DebugInfo::capture(error),
+            meta: None,
+            #[cfg(feature = "axum")]
+            status: StatusCode::BAD_REQUEST,
+            #[cfg(feature = "axum")]
+            headers: None,
         }
     }
 }
 
-impl<D: Serialize, C> Brest<D, C> {
+impl<D: Serialize, C, Meta> Brest<D, C, Meta> {
+    /// Sets the code of an error or fail. Has no effect on a success.
+    #[must_use]
+    pub fn with_code(mut self, code: C) -> Self {
+        match &mut self {
+            Self::Success { .. } => {}
+            Self::Error { code: c, .. } | Self::Fail { code: c, .. } => *c = Some(code),
+        }
+        self
+    }
+
+    /// Replaces the message of an error or fail. Has no effect on a success.
+    #[must_use]
+    pub fn with_message<T: ToString>(mut self, message: T) -> Self {
+        match &mut self {
+            Self::Success { .. } => {}
+            Self::Error { message: m, .. } | Self::Fail { message: m, .. } => {
+                *m = message.to_string()
+            }
+        }
+        self
+    }
+
+    /// Attaches debug information to an error or fail. Has no effect on a success.
+    #[must_use]
+    pub fn with_debug(mut self, debug: DebugInfo) -> Self {
+        match &mut self {
+            Self::Success { .. } => {}
+            Self::Error { debug: d, .. } | Self::Fail { debug: d, .. } => *d = Some(debug),
+        }
+        self
+    }
+
+    #[cfg(feature = "axum")]
+    #[must_use]
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        match &mut self {
+            Self::Success { status: s, .. }
+            | Self::Error { status: s, .. }
+            | Self::Fail { status: s, .. } => *s = status,
+        }
+        self
+    }
+
+    #[inline]
+    pub fn code(&self) -> Option<&C> {
+        match self {
+            Self::Success { .. } => None,
+            Self::Error { code, .. } | Self::Fail { code, .. } => code.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Success { .. } => None,
+            Self::Error { message, .. } | Self::Fail { message, .. } => Some(message),
+        }
+    }
+
+    #[inline]
+    pub fn debug(&self) -> Option<&DebugInfo> {
+        match self {
+            Self::Success { .. } => None,
+            Self::Error { debug, .. } | Self::Fail { debug, .. } => debug.as_ref(),
+        }
+    }
+
+    #[cfg(feature = "axum")]
+    #[inline]
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Success { status, .. } => *status,
+            Self::Error { status, .. } => *status,
+            Self::Fail { status, .. } => *status,
+        }
+    }
+
+    #[must_use]
+    pub fn with_meta(mut self, meta: Meta) -> Self {
+        *self.meta_mut() = Some(meta);
+        self
+    }
+
+    #[inline]
+    pub fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::Success { meta, .. } => meta.as_ref(),
+            Self::Error { meta, .. } => meta.as_ref(),
+            Self::Fail { meta, .. } => meta.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn meta_mut(&mut self) -> &mut Option<Meta> {
+        match self {
+            Self::Success { meta, .. } => meta,
+            Self::Error { meta, .. } => meta,
+            Self::Fail { meta, .. } => meta,
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn is_success(&self) -> bool {
@@ -171,13 +425,14 @@ impl<D: Serialize, C> Brest<D, C> {
                 message,
                 code,
                 status,
+                ..
             } => f(ErrorFields {
                 message,
                 code,
                 status,
             }),
             #[cfg(not(feature = "axum"))]
-            Self::Fail { message, code } => f(ErrorFields { message, code }),
+            Self::Fail { message, code, .. } => f(ErrorFields { message, code }),
             _ => false,
         }
     }
@@ -197,27 +452,136 @@ impl<D: Serialize, C> Brest<D, C> {
                 message,
                 code,
                 status,
+                ..
             } => f(ErrorFields {
                 message,
                 code,
                 status,
             }),
             #[cfg(not(feature = "axum"))]
-            Self::Error { message, code } => f(ErrorFields { message, code }),
+            Self::Error { message, code, .. } => f(ErrorFields { message, code }),
             _ => false,
         }
     }
 }
 
+#[cfg(feature = "axum")]
+impl<D: Serialize, C, Meta> Brest<D, C, Meta> {
+    /// A success with status 201 and a `Location` header.
+    pub fn created<U: AsRef<str>>(data: D, location: U) -> Self {
+        Self::success_status(data, StatusCode::CREATED).with_location(location)
+    }
+
+    /// The headers added to the response, `None` if none were set.
+    #[inline]
+    pub fn headers(&self) -> Option<&HeaderMap> {
+        match self {
+            Self::Success { headers, .. } => headers.as_deref(),
+            Self::Error { headers, .. } => headers.as_deref(),
+            Self::Fail { headers, .. } => headers.as_deref(),
+        }
+    }
+
+    #[inline]
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        // Boxed and created on demand to keep `Brest` small, it's commonly used as `Result::Err`.
+        let headers = match self {
+            Self::Success { headers, .. } => headers,
+            Self::Error { headers, .. } => headers,
+            Self::Fail { headers, .. } => headers,
+        };
+        headers.get_or_insert_with(Default::default)
+    }
+
+    /// Appends a header to the response.
+    #[must_use]
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers_mut().append(name, value);
+        self
+    }
+
+    /// Sets the `Location` header. Values that aren't valid header values are ignored.
+    #[must_use]
+    pub fn with_location<U: AsRef<str>>(mut self, uri: U) -> Self {
+        if let Ok(value) = HeaderValue::from_str(uri.as_ref()) {
+            self.headers_mut().insert(header::LOCATION, value);
+        }
+        self
+    }
+
+    /// Sets a strong `ETag` header, quoting `tag` if needed. Values that aren't valid header
+    /// values are ignored.
+    #[must_use]
+    pub fn with_etag<T: AsRef<str>>(mut self, tag: T) -> Self {
+        let tag = tag.as_ref();
+        let value = if tag.starts_with('"') {
+            HeaderValue::from_str(tag)
+        } else {
+            HeaderValue::from_str(&format!("\"{}\"", tag))
+        };
+        if let Ok(value) = value {
+            self.headers_mut().insert(header::ETAG, value);
+        }
+        self
+    }
+
+    /// Sets the `Retry-After` header, rounded up to whole seconds.
+    #[must_use]
+    pub fn with_retry_after(mut self, retry_after: std::time::Duration) -> Self {
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        self.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        self
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<C, Meta> Brest<(), C, Meta> {
+    /// A success rendered as a bodiless 204 No Content response.
+    pub fn no_content() -> Self {
+        Self::success_status((), StatusCode::NO_CONTENT)
+    }
+
+    /// A success with status 202 and a `Location` header pointing at the job status.
+    pub fn accepted<U: AsRef<str>>(job_url: U) -> Self {
+        Self::success_status((), StatusCode::ACCEPTED).with_location(job_url)
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<D, C, Meta> Brest<D, C, Meta>
+where
+    D: Serialize + serde::de::DeserializeOwned,
+    C: serde::de::DeserializeOwned,
+    Meta: serde::de::DeserializeOwned,
+{
+    /// Deserializes a JSON envelope that was received with `status`.
+    ///
+    /// Plain deserialization can't know the status and falls back to 200, 500 and 400 for
+    /// success, error and fail.
+    pub fn from_slice_with_status(body: &[u8], status: StatusCode) -> serde_json::Result<Self> {
+        serde_json::from_slice::<Self>(body).map(|brest| brest.with_status(status))
+    }
+
+    /// Deserializes the body of `response`, keeping its status.
+    pub fn from_response<B: AsRef<[u8]>>(
+        response: axum::http::Response<B>,
+    ) -> serde_json::Result<Self> {
+        Self::from_slice_with_status(response.body().as_ref(), response.status())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ts", derive(TS))]
 pub struct ErrorFields<C> {
     pub message: String,
     pub code: Option<C>,
     #[cfg(feature = "axum")]
+    #[cfg_attr(feature = "ts", ts(skip))]
     pub status: StatusCode,
 }
 
-impl<D: Serialize, E, C> From<Result<D, E>> for Brest<D, C>
+impl<D: Serialize, E, C, Meta> From<Result<D, E>> for Brest<D, C, Meta>
 where
     E: ToString,
 {
@@ -230,7 +594,7 @@ where
 }
 
 #[cfg(feature = "try")]
-impl<D: Serialize, E, C> FromResidual<Result<D, E>> for Brest<D, C>
+impl<D: Serialize, E, C, Meta> FromResidual<Result<D, E>> for Brest<D, C, Meta>
 where
     E: ToString,
 {
@@ -240,9 +604,9 @@ where
 }
 
 #[cfg(feature = "try")]
-impl<D: Serialize, C> Try for Brest<D, C> {
+impl<D: Serialize, C, Meta> Try for Brest<D, C, Meta> {
     type Output = D;
-    type Residual = Brest<(), C>;
+    type Residual = Brest<(), C, Meta>;
 
     fn from_output(output: Self::Output) -> Self {
         Self::success(output)
@@ -255,64 +619,128 @@ impl<D: Serialize, C> Try for Brest<D, C> {
             Brest::Error {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             } => ControlFlow::Break(Brest::Error {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             }),
             #[cfg(not(feature = "axum"))]
-            Brest::Error { message, code } => ControlFlow::Break(Brest::Error { message, code }),
+            Brest::Error {
+                message,
+                code,
+                debug,
+                meta,
+            } => ControlFlow::Break(Brest::Error {
+                message,
+                code,
+                debug,
+                meta,
+            }),
             #[cfg(feature = "axum")]
             Brest::Fail {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             } => ControlFlow::Break(Brest::Fail {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             }),
             #[cfg(not(feature = "axum"))]
-            Brest::Fail { message, code } => ControlFlow::Break(Brest::Fail { message, code }),
+            Brest::Fail {
+                message,
+                code,
+                debug,
+                meta,
+            } => ControlFlow::Break(Brest::Fail {
+                message,
+                code,
+                debug,
+                meta,
+            }),
         }
     }
 }
 
 #[cfg(feature = "try")]
-impl<D: Serialize, C> FromResidual<Brest<(), C>> for Brest<D, C> {
-    fn from_residual(residual: Brest<(), C>) -> Self {
+impl<D: Serialize, C, Meta> FromResidual<Brest<(), C, Meta>> for Brest<D, C, Meta> {
+    fn from_residual(residual: Brest<(), C, Meta>) -> Self {
         match residual {
             Brest::Success { .. } => unreachable!(),
             #[cfg(feature = "axum")]
             Brest::Error {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             } => Brest::Error {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             },
             #[cfg(not(feature = "axum"))]
-            Brest::Error { message, code } => Brest::Error { message, code },
+            Brest::Error {
+                message,
+                code,
+                debug,
+                meta,
+            } => Brest::Error {
+                message,
+                code,
+                debug,
+                meta,
+            },
             #[cfg(feature = "axum")]
             Brest::Fail {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             } => Brest::Fail {
                 message,
                 code,
+                debug,
+                meta,
                 status,
+                headers,
             },
             #[cfg(not(feature = "axum"))]
-            Brest::Fail { message, code } => Brest::Fail { message, code },
+            Brest::Fail {
+                message,
+                code,
+                debug,
+                meta,
+            } => Brest::Fail {
+                message,
+                code,
+                debug,
+                meta,
+            },
         }
     }
 }
 
-impl<D: Serialize, C, E> From<(Result<D, E>, C)> for Brest<D, C>
+impl<D: Serialize, C, E, Meta> From<(Result<D, E>, C)> for Brest<D, C, Meta>
 where
     E: ToString,
 {
@@ -325,7 +753,7 @@ where
 }
 
 #[cfg(feature = "try")]
-impl<D: Serialize, E, C> FromResidual<(Result<D, E>, C)> for Brest<D, C>
+impl<D: Serialize, E, C, Meta> FromResidual<(Result<D, E>, C)> for Brest<D, C, Meta>
 where
     E: ToString,
 {
@@ -335,7 +763,7 @@ where
 }
 
 #[cfg(feature = "axum")]
-impl<D: Serialize, C, S, E> From<(Result<D, E>, C, S)> for Brest<D, C>
+impl<D: Serialize, C, S, E, Meta> From<(Result<D, E>, C, S)> for Brest<D, C, Meta>
 where
     E: ToString,
     S: Into<StatusCode>,
@@ -349,7 +777,7 @@ where
 }
 
 #[cfg(all(feature = "try", feature = "axum"))]
-impl<D: Serialize, E, S, C> FromResidual<(Result<D, E>, C, S)> for Brest<D, C>
+impl<D: Serialize, E, S, C, Meta> FromResidual<(Result<D, E>, C, S)> for Brest<D, C, Meta>
 where
     E: ToString,
     S: Into<StatusCode>,
@@ -363,94 +791,146 @@ where
     }
 }
 
-impl<D: Serialize, C> From<D> for Brest<D, C> {
+impl<D: Serialize, C, Meta> From<D> for Brest<D, C, Meta> {
     fn from(value: D) -> Self {
         Self::success(value)
     }
 }
 
-#[cfg(all(feature = "axum"))]
-struct BrestResponse<D: Serialize, C>(Brest<D, C>);
+#[cfg(feature = "axum")]
+struct BrestResponse<D: Serialize, C, Meta>(Brest<D, C, Meta>);
 
-#[cfg(all(feature = "axum"))]
-impl<D: Serialize + 'static, C: Serialize> Serialize for BrestResponse<D, C> {
+#[cfg(feature = "axum")]
+impl<D: Serialize, C: Serialize, Meta: Serialize> Serialize for BrestResponse<D, C, Meta> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         let mut s = serializer.serialize_struct("Brest", 2)?;
+        let meta = match &self.0 {
+            Brest::Success { meta, .. } => meta,
+            Brest::Error { meta, .. } => meta,
+            Brest::Fail { meta, .. } => meta,
+        };
         match &self.0 {
             Brest::Success { data, .. } => {
                 s.serialize_field("type", "success")?;
-                if std::any::TypeId::of::<D>() == std::any::TypeId::of::<()>() {
-                    s.serialize_field("data", &())?;
-                } else {
-                    s.serialize_field("data", data)?;
-                }
+                s.serialize_field("data", data)?;
             }
-            Brest::Error { message, code, .. } => {
+            Brest::Error { message, code, debug, .. } => {
                 s.serialize_field("type", "error")?;
                 s.serialize_field("message", message)?;
                 if let Some(c) = code {
                     s.serialize_field("code", c)?;
                 }
+                if let Some(d) = debug {
+                    s.serialize_field("debug", d)?;
+                }
             }
-            Brest::Fail { message, code, .. } => {
+            Brest::Fail { message, code, debug, .. } => {
                 s.serialize_field("type", "fail")?;
                 s.serialize_field("message", message)?;
                 if let Some(c) = code {
                     s.serialize_field("code", c)?;
                 }
+                if let Some(d) = debug {
+                    s.serialize_field("debug", d)?;
+                }
             }
         }
+        if let Some(m) = meta {
+            s.serialize_field("meta", m)?;
+        }
         s.end()
     }
 }
 
-#[cfg(all(feature = "axum"))]
-impl<D: Serialize + 'static, C: Serialize> IntoResponse for Brest<D, C> {
+#[cfg(feature = "axum")]
+impl<D: Serialize, C: Serialize, Meta: Serialize> IntoResponse for Brest<D, C, Meta> {
     fn into_response(self) -> axum::response::Response {
         use axum::Json;
 
-        let status = match &self {
-            Self::Success { status, .. } => *status,
-            Self::Error { status, .. } => *status,
-            Self::Fail { status, .. } => *status,
+        let status = self.status();
+        let mut this = self;
+        let headers = match &mut this {
+            Self::Success { headers, .. } => headers.take(),
+            Self::Error { headers, .. } => headers.take(),
+            Self::Fail { headers, .. } => headers.take(),
         };
 
-        (status, Json(BrestResponse(self))).into_response()
+        // These statuses must not carry a body.
+        if status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+            || status.is_informational()
+        {
+            return match headers {
+                Some(headers) => (status, *headers).into_response(),
+                None => status.into_response(),
+            };
+        }
+
+        match headers {
+            Some(headers) => (status, *headers, Json(BrestResponse(this))).into_response(),
+            None => (status, Json(BrestResponse(this))).into_response(),
+        }
     }
 }
 
-#[cfg(feature = "axum")]
 #[derive(Debug)]
 pub enum BrestErr<C = u32> {
     Error {
         message: String,
         code: Option<C>,
+        #[cfg(feature = "axum")]
         status: StatusCode,
     },
     Fail {
         message: String,
         code: Option<C>,
+        #[cfg(feature = "axum")]
         status: StatusCode,
     },
 }
 
-#[cfg(feature = "axum")]
-impl<C, T: Serialize> From<BrestErr<C>> for Brest<T, C> {
+impl<C, T: Serialize, Meta> From<BrestErr<C>> for Brest<T, C, Meta> {
     fn from(err: BrestErr<C>) -> Self {
         match err {
-            BrestErr::Error { message, code, status } => Brest::Error { message, code, status },
-            BrestErr::Fail { message, code, status } => Brest::Fail { message, code, status },
+            BrestErr::Error {
+                message,
+                code,
+                #[cfg(feature = "axum")]
+                status,
+            } => Brest::Error {
+                message,
+                code,
+                debug: None,
+                meta: None,
+                #[cfg(feature = "axum")]
+                status,
+                #[cfg(feature = "axum")]
+                headers: None,
+            },
+            BrestErr::Fail {
+                message,
+                code,
+                #[cfg(feature = "axum")]
+                status,
+            } => Brest::Fail {
+                message,
+                code,
+                debug: None,
+                meta: None,
+                #[cfg(feature = "axum")]
+                status,
+                #[cfg(feature = "axum")]
+                headers: None,
+            },
         }
     }
 }
 
-#[cfg(feature = "axum")]
 impl<C: std::fmt::Debug> std::error::Error for BrestErr<C> {}
 
-#[cfg(feature = "axum")]
 impl<C: std::fmt::Debug> std::fmt::Display for BrestErr<C> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -460,8 +940,94 @@ impl<C: std::fmt::Debug> std::fmt::Display for BrestErr<C> {
     }
 }
 
+impl<D: Serialize, C, Meta> Brest<D, C, Meta> {
+    /// Splits off the data of a success, or the error or fail as a [`BrestErr`], which
+    /// implements [`std::error::Error`]. `debug`, `meta` and headers are dropped.
+    pub fn into_result(self) -> Result<D, BrestErr<C>> {
+        match self {
+            Self::Success { data, .. } => Ok(data),
+            Self::Error {
+                message,
+                code,
+                #[cfg(feature = "axum")]
+                status,
+                ..
+            } => Err(BrestErr::Error {
+                message,
+                code,
+                #[cfg(feature = "axum")]
+                status,
+            }),
+            Self::Fail {
+                message,
+                code,
+                #[cfg(feature = "axum")]
+                status,
+                ..
+            } => Err(BrestErr::Fail {
+                message,
+                code,
+                #[cfg(feature = "axum")]
+                status,
+            }),
+        }
+    }
+}
+
+impl<D: Serialize, C: Debug, Meta> Brest<D, C, Meta> {
+    /// A one line summary for logs and terminals: `Success`, or `Error` / `Fail` followed by
+    /// the status and code, if any, and the message, e.g.
+    /// `Fail (404 Not Found, code 7): User not found`.
+    pub fn display(&self) -> BrestDisplay<'_, D, C, Meta> {
+        BrestDisplay(self)
+    }
+}
+
+/// Implements [`Display`](std::fmt::Display) for a [`Brest`], see [`Brest::display`].
+pub struct BrestDisplay<'a, D, C, Meta>(&'a Brest<D, C, Meta>);
+
+impl<D: Serialize, C: Debug, Meta> std::fmt::Display for BrestDisplay<'_, D, C, Meta> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (kind, message, code) = match self.0 {
+            Brest::Success { .. } => ("Success", None, None),
+            Brest::Error { message, code, .. } => ("Error", Some(message), code.as_ref()),
+            Brest::Fail { message, code, .. } => ("Fail", Some(message), code.as_ref()),
+        };
+        f.write_str(kind)?;
+
+        let mut details = Vec::new();
+        #[cfg(feature = "axum")]
+        details.push(self.0.status().to_string());
+        if let Some(code) = code {
+            details.push(format!("code {:?}", code));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+
+        match message {
+            Some(message) => write!(f, ": {}", message),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lets `main` return a `Brest`: a success exits with 0, an error with 1 and a fail with 2.
+/// Errors and fails are printed to stderr.
+impl<D: Serialize, C: Debug, Meta> std::process::Termination for Brest<D, C, Meta> {
+    fn report(self) -> std::process::ExitCode {
+        let code = match &self {
+            Self::Success { .. } => return std::process::ExitCode::SUCCESS,
+            Self::Error { .. } => 1,
+            Self::Fail { .. } => 2,
+        };
+        eprintln!("{}", self.display());
+        std::process::ExitCode::from(code)
+    }
+}
+
 #[cfg(feature = "try")]
-impl<D: Serialize, C, U> FromResidual<Result<U, Self>> for Brest<D, C> {
+impl<D: Serialize, C, U, Meta> FromResidual<Result<U, Self>> for Brest<D, C, Meta> {
     fn from_residual(residual: Result<U, Self>) -> Self {
         residual.err().unwrap()
     }
@@ -470,7 +1036,6 @@ impl<D: Serialize, C, U> FromResidual<Result<U, Self>> for Brest<D, C> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json;
 
     #[test]
     fn test_success_construction() {
@@ -651,6 +1216,132 @@ mod tests {
         assert!(brest.is_success());
     }
 
+    #[test]
+    fn test_display() {
+        #[cfg(not(feature = "axum"))]
+        {
+            assert_eq!(Brest::<u32>::success(1).display().to_string(), "Success");
+            assert_eq!(Brest::<()>::fail_code("Not found", 7).display().to_string(), "Fail (code 7): Not found");
+            assert_eq!(Brest::<()>::error("Boom").display().to_string(), "Error: Boom");
+        }
+        #[cfg(feature = "axum")]
+        {
+            assert_eq!(Brest::<u32>::success(1).display().to_string(), "Success (200 OK)");
+            assert_eq!(
+                Brest::<()>::fail_code("Not found", 7).display().to_string(),
+                "Fail (400 Bad Request, code 7): Not found"
+            );
+        }
+    }
+
+    #[test]
+    fn test_into_result() {
+        assert_eq!(Brest::<u32>::success(1).into_result().unwrap(), 1);
+
+        let err = Brest::<u32>::fail_code("Not found", 7).into_result().unwrap_err();
+        assert!(matches!(err, BrestErr::Fail { code: Some(7), .. }));
+        let err: Box<dyn std::error::Error> = Box::new(err);
+        assert_eq!(err.to_string(), "Fail: Not found");
+    }
+
+    #[test]
+    fn test_termination() {
+        use std::process::{ExitCode, Termination};
+
+        assert_eq!(Brest::<()>::success(()).report(), ExitCode::SUCCESS);
+        assert_eq!(Brest::<()>::error("Boom").report(), ExitCode::from(1));
+        assert_eq!(Brest::<()>::fail("Nope").report(), ExitCode::from(2));
+    }
+
+    #[test]
+    fn test_meta_serialization() {
+        let brest = Brest::<u32, u32, String>::success(1).with_meta("req-1".to_string());
+        let json = serde_json::to_string(&brest).unwrap();
+        assert_eq!(json, r#"{"type":"success","data":1,"meta":"req-1"}"#);
+
+        let brest = Brest::<(), u32, String>::error_code("error", 500).with_meta("req-1".to_string());
+        let json = serde_json::to_string(&brest).unwrap();
+        assert_eq!(json, r#"{"type":"error","message":"error","code":500,"meta":"req-1"}"#);
+    }
+
+    #[test]
+    fn test_meta_deserialization() {
+        let json = r#"{"type":"fail","message":"fail","meta":"req-1"}"#;
+        let brest: Brest<(), u32, String> = serde_json::from_str(json).unwrap();
+        assert_eq!(brest.meta().map(String::as_str), Some("req-1"));
+
+        let json = r#"{"type":"fail","message":"fail"}"#;
+        let brest: Brest<(), u32, String> = serde_json::from_str(json).unwrap();
+        assert_eq!(brest.meta(), None);
+
+        // Meta stamped by middleware doesn't break clients without a meta type.
+        let json = r#"{"type":"fail","message":"fail","meta":{"request_id":"req-1"}}"#;
+        let brest: Brest = serde_json::from_str(json).unwrap();
+        assert_eq!(brest.meta(), None);
+        assert!(serde_json::from_str::<Brest<(), u32, u32>>(json).is_err());
+    }
+
+    #[test]
+    fn test_chained_modifiers() {
+        let brest = Brest::<(), u32>::fail("fail")
+            .with_code(400)
+            .with_message("changed")
+            .with_debug(DebugInfo::default());
+        assert_eq!(brest.code(), Some(&400));
+        assert_eq!(brest.message(), Some("changed"));
+        assert_eq!(brest.debug(), Some(&DebugInfo::default()));
+
+        let brest = Brest::<u32, u32>::success(1).with_code(400).with_message("ignored");
+        assert_eq!(brest.code(), None);
+        assert_eq!(brest.message(), None);
+    }
+
+    #[derive(Debug)]
+    struct Outer(Inner);
+
+    #[derive(Debug)]
+    struct Inner;
+
+    impl std::fmt::Display for Outer {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "outer")
+        }
+    }
+
+    impl std::error::Error for Outer {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    impl std::fmt::Display for Inner {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "inner")
+        }
+    }
+
+    impl std::error::Error for Inner {}
+
+    #[test]
+    fn test_error_report_serialization() {
+        let brest = Brest::<(), u32>::error_report(&Outer(Inner)).with_code(500);
+        let json = serde_json::to_string(&brest).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"error","message":"outer","code":500,"debug":{"causes":["inner"]}}"#
+        );
+    }
+
+    #[test]
+    fn test_fail_report_deserialization() {
+        let json = r#"{"type":"fail","message":"outer","debug":{"causes":["inner"]}}"#;
+        let brest: Brest<(), u32> = serde_json::from_str(json).unwrap();
+        match (brest, Brest::<(), u32>::fail_report(&Outer(Inner))) {
+            (Brest::Fail { debug: a, .. }, Brest::Fail { debug: b, .. }) => assert_eq!(a, b),
+            _ => panic!("Expected Fail"),
+        }
+    }
+
     #[cfg(feature = "try")]
     mod try_tests {
         use super::*;
@@ -767,6 +1458,14 @@ mod tests {
             assert_eq!(json, r#"{"type":"error","message":"error","code":500}"#);
         }
 
+        #[test]
+        fn test_brest_response_meta_serialization() {
+            let brest = Brest::<(), u32, String>::fail_code("fail", 400).with_meta("req-1".to_string());
+            let response = BrestResponse(brest);
+            let json = serde_json::to_string(&response).unwrap();
+            assert_eq!(json, r#"{"type":"fail","message":"fail","code":400,"meta":"req-1"}"#);
+        }
+
         #[test]
         fn test_brest_response_fail_serialization() {
             let brest = Brest::<(), u32>::fail_code("fail", 400);
@@ -782,6 +1481,96 @@ mod tests {
             assert_eq!(response.status(), StatusCode::OK);
         }
 
+        #[test]
+        fn test_with_status() {
+            let brest = Brest::<(), u32>::fail("fail")
+                .with_code(404)
+                .with_status(StatusCode::NOT_FOUND)
+                .with_location("/elsewhere");
+            assert_eq!(brest.status(), StatusCode::NOT_FOUND);
+            assert_eq!(brest.code(), Some(&404));
+            assert_eq!(brest.headers().unwrap()[header::LOCATION], "/elsewhere");
+        }
+
+        #[test]
+        fn test_deserialized_status() {
+            let brest: Brest = serde_json::from_str(r#"{"type":"success","data":null}"#).unwrap();
+            assert_eq!(brest.status(), StatusCode::OK);
+            let brest: Brest = serde_json::from_str(r#"{"type":"error","message":"x"}"#).unwrap();
+            assert_eq!(brest.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            let brest: Brest = serde_json::from_str(r#"{"type":"fail","message":"x"}"#).unwrap();
+            assert_eq!(brest.status(), StatusCode::BAD_REQUEST);
+        }
+
+        #[tokio::test]
+        async fn test_from_response() {
+            let original = Brest::<(), u32>::fail_code_status("Gone", 7, StatusCode::GONE);
+            let response = original.into_response();
+            let (parts, body) = response.into_parts();
+            let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+
+            let brest = Brest::<(), u32>::from_response(axum::http::Response::from_parts(parts, bytes)).unwrap();
+            assert_eq!(brest.status(), StatusCode::GONE);
+            assert_eq!(brest.code(), Some(&7));
+
+            let brest = Brest::<(), u32>::from_slice_with_status(br#"{"type":"error","message":"x"}"#, StatusCode::BAD_GATEWAY).unwrap();
+            assert_eq!(brest.status(), StatusCode::BAD_GATEWAY);
+
+            // Meta doesn't need to implement Default.
+            #[derive(Debug, PartialEq, Deserialize)]
+            struct Trace(String);
+            let brest = Brest::<(), u32, Trace>::from_slice_with_status(br#"{"type":"fail","message":"x","meta":"t"}"#, StatusCode::CONFLICT).unwrap();
+            assert_eq!(brest.meta(), Some(&Trace("t".to_string())));
+        }
+
+        #[tokio::test]
+        async fn test_no_content() {
+            let response = Brest::<(), u32>::no_content().with_etag("v1").into_response();
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+            assert_eq!(response.headers()[header::ETAG], "\"v1\"");
+            assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert!(bytes.is_empty());
+
+            let response = Brest::<u32, u32>::success_status(1, StatusCode::NO_CONTENT).into_response();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert!(bytes.is_empty());
+        }
+
+        #[test]
+        fn test_created() {
+            let response = Brest::<u32, u32>::created(1, "/items/1").into_response();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            assert_eq!(response.headers()[header::LOCATION], "/items/1");
+        }
+
+        #[test]
+        fn test_accepted() {
+            let response = Brest::<(), u32>::accepted("/jobs/1").into_response();
+            assert_eq!(response.status(), StatusCode::ACCEPTED);
+            assert_eq!(response.headers()[header::LOCATION], "/jobs/1");
+        }
+
+        #[test]
+        fn test_headers_into_response() {
+            let response = Brest::<(), u32>::error_status("busy", StatusCode::SERVICE_UNAVAILABLE)
+                .with_retry_after(std::time::Duration::from_millis(2500))
+                .with_header(HeaderName::from_static("x-custom"), HeaderValue::from_static("a"))
+                .with_header(HeaderName::from_static("x-custom"), HeaderValue::from_static("b"))
+                .into_response();
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+            assert_eq!(response.headers().get_all("x-custom").iter().count(), 2);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        }
+
+        #[test]
+        fn test_headers_not_serialized() {
+            let brest = Brest::<(), u32>::fail("fail").with_location("/x");
+            let json = serde_json::to_string(&brest).unwrap();
+            assert_eq!(json, r#"{"type":"fail","message":"fail"}"#);
+        }
+
         #[test]
         fn test_brest_err_from() {
             let err = BrestErr::Error {
@@ -0,0 +1,178 @@
+use std::any::Any;
+use std::future::{poll_fn, Future};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::task::{Context, Poll};
+
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::BoxFuture;
+use crate::Brest;
+
+/// Converts panics in the inner service into a Brest error with status 500.
+///
+/// The panic payload is never sent to the client; it is recorded with `tracing` instead.
+#[derive(Debug, Clone)]
+pub struct CatchPanicLayer<C = u32> {
+    message: String,
+    code: Option<C>,
+}
+
+impl<C> CatchPanicLayer<C> {
+    pub fn new() -> Self {
+        Self {
+            message: "Internal server error".to_string(),
+            code: None,
+        }
+    }
+
+    /// Uses `message` as the redacted message sent to the client.
+    #[must_use]
+    pub fn message<M: ToString>(mut self, message: M) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn code(mut self, code: C) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    fn response(&self) -> Response
+    where
+        C: Serialize + Clone,
+    {
+        match self.code.clone() {
+            Some(code) => Brest::<(), C>::error_code_status(
+                &self.message,
+                code,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            None => Brest::<(), C>::error_status(&self.message, StatusCode::INTERNAL_SERVER_ERROR),
+        }
+        .into_response()
+    }
+}
+
+impl<C> Default for CatchPanicLayer<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, C: Clone> Layer<S> for CatchPanicLayer<C> {
+    type Service = CatchPanicService<S, C>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CatchPanicService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchPanicService<S, C = u32> {
+    inner: S,
+    layer: CatchPanicLayer<C>,
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+impl<S, C> Service<Request> for CatchPanicService<S, C>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+    C: Serialize + Clone + Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let layer = self.layer.clone();
+        let future = match catch_unwind(AssertUnwindSafe(|| self.inner.call(req))) {
+            Ok(future) => future,
+            Err(payload) => {
+                tracing::error!(panic = payload_message(&*payload), "service panicked");
+                return Box::pin(async move { Ok(layer.response()) });
+            }
+        };
+
+        Box::pin(async move {
+            let mut future = Box::pin(future);
+            let result = poll_fn(|cx| {
+                match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                    Ok(Poll::Ready(result)) => Poll::Ready(Ok(result)),
+                    Ok(Poll::Pending) => Poll::Pending,
+                    Err(payload) => Poll::Ready(Err(payload)),
+                }
+            })
+            .await;
+
+            match result {
+                Ok(result) => result,
+                Err(payload) => {
+                    tracing::error!(panic = payload_message(&*payload), "handler panicked");
+                    Ok(layer.response())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body;
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    async fn panicking() -> Brest {
+        panic!("secret")
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        let app = Router::new()
+            .route("/", get(panicking))
+            .layer(CatchPanicLayer::<u32>::new().code(9000));
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"error","message":"Internal server error","code":9000}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic_passes_through() {
+        let app = Router::new()
+            .route("/", get(|| async { Brest::<u32>::success(1) }))
+            .layer(CatchPanicLayer::<u32>::new().message("oops"));
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, r#"{"type":"success","data":1}"#);
+    }
+}
@@ -0,0 +1,219 @@
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::{fnv1a, is_json, BoxFuture, FNV_OFFSET};
+use crate::Brest;
+
+/// Adds a strong `ETag` and `Cache-Control` to successful Brest responses of `GET` and `HEAD`
+/// requests and answers matching `If-None-Match` requests with `304 Not Modified`.
+///
+/// The ETag is computed over the serialized body, unless the handler already set one, for
+/// example with [`Brest::with_etag`](crate::Brest::with_etag).
+#[derive(Debug, Clone)]
+pub struct ConditionalGetLayer {
+    cache_control: HeaderValue,
+}
+
+impl ConditionalGetLayer {
+    pub fn new() -> Self {
+        Self {
+            cache_control: HeaderValue::from_static("no-cache"),
+        }
+    }
+
+    #[must_use]
+    pub fn cache_control(mut self, cache_control: HeaderValue) -> Self {
+        self.cache_control = cache_control;
+        self
+    }
+}
+
+impl Default for ConditionalGetLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for ConditionalGetLayer {
+    type Service = ConditionalGetService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConditionalGetService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConditionalGetService<S> {
+    inner: S,
+    layer: ConditionalGetLayer,
+}
+
+/// A strong ETag over `bytes`, stable across processes.
+pub fn compute_etag(bytes: &[u8]) -> HeaderValue {
+    let hash = fnv1a(FNV_OFFSET, bytes);
+    HeaderValue::from_str(&format!("\"{:x}-{:016x}\"", bytes.len(), hash))
+        .expect("hex is a valid header value")
+}
+
+fn opaque_tag(tag: &str) -> &str {
+    tag.trim().trim_start_matches("W/")
+}
+
+/// Weak comparison of `etag` against an `If-None-Match` header.
+fn none_match(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque_tag(tag) == opaque_tag(etag))
+}
+
+impl<S> Service<Request> for ConditionalGetService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let request_headers = req.headers().clone();
+        let cache_control = self.layer.cache_control.clone();
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let response = future.await?;
+            if response.status() != StatusCode::OK || !is_json(&response) {
+                return Ok(response);
+            }
+
+            let (mut parts, body) = response.into_parts();
+            let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(e) => return Ok(Brest::<()>::error(e).into_response()),
+            };
+            let success = serde_json::from_slice::<Value>(&bytes)
+                .is_ok_and(|value| value.get("type").and_then(Value::as_str) == Some("success"));
+            if !success {
+                return Ok(Response::from_parts(parts, Body::from(bytes)));
+            }
+
+            let etag = match parts.headers.get(header::ETAG) {
+                Some(etag) => etag.clone(),
+                None => compute_etag(&bytes),
+            };
+            parts.headers.insert(header::ETAG, etag.clone());
+            parts
+                .headers
+                .entry(header::CACHE_CONTROL)
+                .or_insert(cache_control);
+
+            if none_match(&request_headers, &etag) {
+                parts.status = StatusCode::NOT_MODIFIED;
+                parts.headers.remove(header::CONTENT_TYPE);
+                parts.headers.remove(header::CONTENT_LENGTH);
+                return Ok(Response::from_parts(parts, Body::empty()));
+            }
+
+            Ok(Response::from_parts(parts, Body::from(bytes)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(|| async { Brest::<u32>::success(1) }))
+            .route("/tagged", get(|| async { Brest::<u32>::success(1).with_etag("v7") }))
+            .route("/fail", get(|| async { Brest::<()>::fail_status("nope", StatusCode::OK) }))
+            .route("/text", get(|| async { "text".into_response() }))
+            .layer(ConditionalGetLayer::new())
+    }
+
+    async fn get_with(uri: &str, if_none_match: Option<&str>) -> Response {
+        let mut req = Request::builder().uri(uri);
+        if let Some(tag) = if_none_match {
+            req = req.header(header::IF_NONE_MATCH, tag);
+        }
+        app().oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_etag_and_not_modified() {
+        let response = get_with("/", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert_eq!(etag, compute_etag(br#"{"type":"success","data":1}"#));
+
+        let response = get_with("/", Some(&format!("\"other\", W/{}", etag))).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert!(body(response).await.is_empty());
+
+        let response = get_with("/", Some("\"other\"")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_user_etag() {
+        let response = get_with("/tagged", Some("\"v7\"")).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], "\"v7\"");
+    }
+
+    #[tokio::test]
+    async fn test_rewritten_envelope() {
+        use crate::middleware::MetaLayer;
+        use serde_json::Map;
+
+        let app = Router::new()
+            .route("/", get(|| async { Brest::<u32>::success(1) }))
+            .layer(MetaLayer::new(|_: &axum::http::response::Parts| {
+                Map::from_iter([("version".to_string(), Value::from(2))])
+            }))
+            .layer(ConditionalGetLayer::new());
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert!(response.headers().get(header::ETAG).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_only_success_envelopes() {
+        let response = get_with("/fail", Some("*")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+
+        let response = get_with("/text", Some("*")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+    }
+}
@@ -0,0 +1,248 @@
+use std::task::{Context, Poll};
+
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::{is_envelope, is_json, BoxFuture};
+use crate::Brest;
+
+/// Rewrites error responses that don't carry a Brest body into a Brest envelope.
+///
+/// Responses with a 4xx status become `fail`, 5xx become `error`, keeping the original status
+/// and headers. Successful responses and responses that already are Brest envelopes are left
+/// untouched.
+///
+/// The body of 5xx responses is replaced with the canonical reason unless
+/// [`redact_server_errors`](Self::redact_server_errors) is turned off, as it may leak internals.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvelopeLayer {
+    redact_server_errors: bool,
+    max_body: usize,
+}
+
+impl EnvelopeLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the body of 5xx responses with the canonical reason instead of using it as message.
+    #[must_use]
+    pub fn redact_server_errors(mut self, redact: bool) -> Self {
+        self.redact_server_errors = redact;
+        self
+    }
+
+    /// Limits the error bodies buffered for rewriting. Larger bodies are replaced with the
+    /// canonical reason.
+    #[must_use]
+    pub fn max_body(mut self, max_body: usize) -> Self {
+        self.max_body = max_body;
+        self
+    }
+}
+
+impl Default for EnvelopeLayer {
+    fn default() -> Self {
+        Self {
+            redact_server_errors: true,
+            max_body: 64 * 1024,
+        }
+    }
+}
+
+impl<S> Layer<S> for EnvelopeLayer {
+    type Service = EnvelopeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        EnvelopeService { inner, layer: *self }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvelopeService<S> {
+    inner: S,
+    layer: EnvelopeLayer,
+}
+
+impl<S> Service<Request> for EnvelopeService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let future = self.inner.call(req);
+        let layer = self.layer;
+        Box::pin(async move {
+            let response = future.await?;
+            Ok(envelope(response, layer).await)
+        })
+    }
+}
+
+fn reason(status: StatusCode) -> String {
+    status
+        .canonical_reason()
+        .unwrap_or("Unknown error")
+        .to_string()
+}
+
+async fn envelope(response: Response, layer: EnvelopeLayer) -> Response {
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let json = is_json(&response);
+    let is_text = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/plain"));
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = if body.size_hint().lower() > layer.max_body as u64 {
+        Bytes::new()
+    } else {
+        // A body that fails to read or turns out larger than announced is treated as too large.
+        axum::body::to_bytes(body, layer.max_body).await.unwrap_or_default()
+    };
+
+    if json
+        && serde_json::from_slice::<Value>(&bytes)
+            .map(|value| is_envelope(&value))
+            .unwrap_or(false)
+    {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+
+    let text = std::str::from_utf8(&bytes).map(str::trim).unwrap_or_default();
+    let redact = status.is_server_error() && layer.redact_server_errors;
+    let message = if is_text && !text.is_empty() && !redact {
+        text.to_string()
+    } else {
+        reason(status)
+    };
+
+    let rewritten = if status.is_server_error() {
+        Brest::<()>::error_status(message, status)
+    } else {
+        Brest::<()>::fail_status(message, status)
+    }
+    .into_response();
+
+    let (rewritten, body) = rewritten.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    if let Some(content_type) = rewritten.headers.get(header::CONTENT_TYPE) {
+        parts.headers.insert(header::CONTENT_TYPE, content_type.clone());
+    }
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn app(layer: EnvelopeLayer) -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "fine" }))
+            .route("/text", get(|| async { (StatusCode::UNAUTHORIZED, "Missing token") }))
+            .route("/empty", get(|| async { StatusCode::REQUEST_TIMEOUT }))
+            .route("/server", get(|| async { (StatusCode::BAD_GATEWAY, "upstream at 10.0.0.1 down") }))
+            .route("/large", get(|| async { (StatusCode::CONFLICT, "x".repeat(100)) }))
+            .route("/brest", get(|| async { Brest::<()>::fail_code("nope", 7) }))
+            .layer(layer)
+    }
+
+    async fn get_body(layer: EnvelopeLayer, uri: &str) -> (StatusCode, String) {
+        let response = app(layer)
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        (response.status(), body(response).await)
+    }
+
+    #[tokio::test]
+    async fn test_wraps_text_error() {
+        assert_eq!(
+            get_body(EnvelopeLayer::new(), "/text").await,
+            (StatusCode::UNAUTHORIZED, r#"{"type":"fail","message":"Missing token"}"#.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wraps_empty_error() {
+        assert_eq!(
+            get_body(EnvelopeLayer::new(), "/empty").await,
+            (StatusCode::REQUEST_TIMEOUT, r#"{"type":"fail","message":"Request Timeout"}"#.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wraps_server_error() {
+        assert_eq!(
+            get_body(EnvelopeLayer::new(), "/server").await,
+            (StatusCode::BAD_GATEWAY, r#"{"type":"error","message":"Bad Gateway"}"#.to_string())
+        );
+        assert_eq!(
+            get_body(EnvelopeLayer::new().redact_server_errors(false), "/server").await,
+            (StatusCode::BAD_GATEWAY, r#"{"type":"error","message":"upstream at 10.0.0.1 down"}"#.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_body_not_used() {
+        assert_eq!(
+            get_body(EnvelopeLayer::new().max_body(10), "/large").await,
+            (StatusCode::CONFLICT, r#"{"type":"fail","message":"Conflict"}"#.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_streamed_body_not_used() {
+        let app = Router::new()
+            .route(
+                "/stream",
+                get(|| async {
+                    // A stream has no exact size hint, so the limit is only hit while reading.
+                    let body = Body::from("x".repeat(24)).into_data_stream();
+                    (StatusCode::CONFLICT, Body::from_stream(body))
+                }),
+            )
+            .layer(EnvelopeLayer::new().max_body(10));
+        let response = app
+            .oneshot(Request::builder().uri("/stream").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(body(response).await, r#"{"type":"fail","message":"Conflict"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_leaves_success_and_brest_untouched() {
+        assert_eq!(
+            get_body(EnvelopeLayer::new(), "/ok").await,
+            (StatusCode::OK, "fine".to_string())
+        );
+        assert_eq!(
+            get_body(EnvelopeLayer::new(), "/brest").await,
+            (StatusCode::BAD_REQUEST, r#"{"type":"fail","message":"nope","code":7}"#.to_string())
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::error::Error;
+use std::future::{ready, Ready};
+use std::time::Duration;
+
+use axum::error_handling::HandleErrorLayer;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::BoxError;
+use serde::Serialize;
+use tower::load_shed::error::Overloaded;
+use tower::timeout::error::Elapsed;
+
+use crate::Brest;
+
+/// Maps errors of tower middleware to Brest envelopes, for use with [`HandleErrorLayer`].
+///
+/// - [`Elapsed`] becomes a `fail` with status 408 (or 504, see [`TowerErrorHandler::timeout_status`])
+/// - [`Overloaded`] becomes an `error` with status 503 and a `Retry-After` header
+/// - anything else becomes a redacted `error` with status 500
+///
+/// Errors wrapped by other middleware, such as `tower::buffer`, are matched through their
+/// `source()` chain. Each case carries a stable code, [`TowerErrorHandler::TIMEOUT`],
+/// [`TowerErrorHandler::OVERLOADED`] and [`TowerErrorHandler::INTERNAL`] by default.
+#[derive(Debug, Clone)]
+pub struct TowerErrorHandler<C = u32> {
+    timeout_status: StatusCode,
+    retry_after: Duration,
+    timeout_code: C,
+    overloaded_code: C,
+    internal_code: C,
+}
+
+impl TowerErrorHandler {
+    /// Code of the fail returned when a `tower::timeout` elapsed.
+    pub const TIMEOUT: u32 = 408;
+    /// Code of the error returned when a `tower::load_shed` service is overloaded.
+    pub const OVERLOADED: u32 = 503;
+    /// Code of the error returned for any other middleware error.
+    pub const INTERNAL: u32 = 500;
+
+    pub fn new() -> Self {
+        Self::with_codes(Self::TIMEOUT, Self::OVERLOADED, Self::INTERNAL)
+    }
+}
+
+impl<C> TowerErrorHandler<C> {
+    /// A handler using codes of another type than `u32`.
+    pub fn with_codes(timeout_code: C, overloaded_code: C, internal_code: C) -> Self {
+        Self {
+            timeout_status: StatusCode::REQUEST_TIMEOUT,
+            retry_after: Duration::from_secs(1),
+            timeout_code,
+            overloaded_code,
+            internal_code,
+        }
+    }
+
+    #[must_use]
+    pub fn timeout_status(mut self, status: StatusCode) -> Self {
+        self.timeout_status = status;
+        self
+    }
+
+    /// Sets the `Retry-After` sent with overloaded responses, rounded up to whole seconds.
+    #[must_use]
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Code of the envelope returned when a `tower::timeout` elapsed.
+    #[must_use]
+    pub fn timeout_code(mut self, code: C) -> Self {
+        self.timeout_code = code;
+        self
+    }
+
+    /// Code of the envelope returned when a `tower::load_shed` service is overloaded.
+    #[must_use]
+    pub fn overloaded_code(mut self, code: C) -> Self {
+        self.overloaded_code = code;
+        self
+    }
+
+    /// Code of the envelope returned for any other middleware error.
+    #[must_use]
+    pub fn internal_code(mut self, code: C) -> Self {
+        self.internal_code = code;
+        self
+    }
+}
+
+impl<C: Serialize + Clone> TowerErrorHandler<C> {
+    pub fn handle(&self, err: &BoxError) -> Response {
+        let mut source: Option<&(dyn Error + 'static)> = Some(err.as_ref());
+        while let Some(err) = source {
+            if err.is::<Elapsed>() {
+                return Brest::<(), C>::fail_code_status(
+                    "Request timed out",
+                    self.timeout_code.clone(),
+                    self.timeout_status,
+                )
+                .into_response();
+            }
+            if err.is::<Overloaded>() {
+                return Brest::<(), C>::error_code_status(
+                    "Service overloaded",
+                    self.overloaded_code.clone(),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )
+                .with_retry_after(self.retry_after)
+                .into_response();
+            }
+            source = err.source();
+        }
+
+        tracing::error!(error = %err, "unhandled middleware error");
+        Brest::<(), C>::error_code_status(
+            "Internal server error",
+            self.internal_code.clone(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+        .into_response()
+    }
+
+    /// A [`HandleErrorLayer`] using this handler.
+    pub fn layer(
+        self,
+    ) -> HandleErrorLayer<impl Fn(BoxError) -> Ready<Response> + Clone + Send + Sync + 'static, ()>
+    where
+        C: Send + Sync + 'static,
+    {
+        HandleErrorLayer::new(move |err: BoxError| ready(self.handle(&err)))
+    }
+}
+
+impl Default for TowerErrorHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handler for [`HandleErrorLayer`] using the default [`TowerErrorHandler`].
+pub async fn handle_tower_error(err: BoxError) -> Response {
+    TowerErrorHandler::new().handle(&err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body;
+    use axum::body::Body;
+    use axum::http::header;
+    use axum::extract::Request;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::{ServiceBuilder, ServiceExt};
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let app = Router::new()
+            .route("/", get(std::future::pending::<()>))
+            .layer(
+                ServiceBuilder::new()
+                    .layer(HandleErrorLayer::new(handle_tower_error))
+                    .timeout(Duration::ZERO),
+            );
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"fail","message":"Request timed out","code":408}"#
+        );
+    }
+
+    #[test]
+    fn test_overloaded() {
+        let handler = TowerErrorHandler::new().retry_after(Duration::from_millis(1500));
+        let response = handler.handle(&BoxError::from(Overloaded::new()));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+
+    #[test]
+    fn test_timeout_status() {
+        let handler = TowerErrorHandler::new().timeout_status(StatusCode::GATEWAY_TIMEOUT);
+        let response = handler.handle(&BoxError::from(Elapsed::new()));
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_unknown() {
+        let response = TowerErrorHandler::new().handle(&BoxError::from("db password is hunter2"));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"error","message":"Internal server error","code":500}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_codes() {
+        let handler = TowerErrorHandler::with_codes("timeout", "overloaded", "internal")
+            .internal_code("unknown");
+        let response = handler.handle(&BoxError::from(Elapsed::new()));
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"fail","message":"Request timed out","code":"timeout"}"#
+        );
+        let response = handler.handle(&BoxError::from(Overloaded::new()));
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"error","message":"Service overloaded","code":"overloaded"}"#
+        );
+        let response = handler.handle(&BoxError::from("boom"));
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"error","message":"Internal server error","code":"unknown"}"#
+        );
+    }
+}
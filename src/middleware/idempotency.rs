@@ -0,0 +1,694 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::Request;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::{fnv1a, BoxFuture, FNV_OFFSET};
+use crate::Brest;
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// A response recorded for an idempotency key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// The outcome of [`IdempotencyStore::begin`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyState {
+    /// The key is new, the request should be processed.
+    Started,
+    /// A request with this key is still being processed.
+    InFlight,
+    /// The key was used for a request with a different fingerprint.
+    Mismatch,
+    /// The request was already processed, the stored response should be replayed.
+    Completed(StoredResponse),
+}
+
+/// Records idempotency keys and the responses produced for them.
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Claims `key` for a request with `fingerprint`, unless it is already known.
+    fn begin(&self, key: &str, fingerprint: u64) -> impl Future<Output = IdempotencyState> + Send;
+
+    /// Stores the response for a key claimed with [`IdempotencyStore::begin`].
+    fn complete(&self, key: &str, response: StoredResponse) -> impl Future<Output = ()> + Send;
+
+    /// Releases a claimed key without storing a response, so the request can be retried.
+    ///
+    /// When the request is dropped, the returned future is polled only once. Stores that can't
+    /// release the key right away should hand the work off to a task of their own.
+    fn abort(&self, key: &str) -> impl Future<Output = ()> + Send;
+}
+
+#[derive(Debug)]
+struct Entry {
+    fingerprint: u64,
+    response: Option<StoredResponse>,
+    created: Instant,
+}
+
+#[derive(Debug)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    prune_at: usize,
+}
+
+/// An in-memory [`IdempotencyStore`], local to the process. Keys expire after `ttl`.
+#[derive(Debug)]
+pub struct MemoryIdempotencyStore {
+    entries: Mutex<Entries>,
+    ttl: Duration,
+}
+
+impl MemoryIdempotencyStore {
+    const PRUNE_THRESHOLD: usize = 10_000;
+
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                map: HashMap::new(),
+                prune_at: Self::PRUNE_THRESHOLD,
+            }),
+            ttl,
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn begin_at(&self, key: &str, fingerprint: u64, now: Instant) -> IdempotencyState {
+        let ttl = self.ttl;
+        let expired = |entry: &Entry| now.saturating_duration_since(entry.created) >= ttl;
+
+        let mut entries = self.entries();
+        if entries.map.len() > entries.prune_at {
+            entries.map.retain(|_, entry| !expired(entry));
+            // Prune again once the map doubled, so the scan stays amortized over the inserts.
+            entries.prune_at = (entries.map.len() * 2).max(Self::PRUNE_THRESHOLD);
+        }
+
+        match entries.map.get(key).filter(|entry| !expired(entry)) {
+            Some(entry) if entry.fingerprint != fingerprint => IdempotencyState::Mismatch,
+            Some(Entry {
+                response: Some(response),
+                ..
+            }) => IdempotencyState::Completed(response.clone()),
+            Some(_) => IdempotencyState::InFlight,
+            None => {
+                entries.map.insert(
+                    key.to_string(),
+                    Entry {
+                        fingerprint,
+                        response: None,
+                        created: now,
+                    },
+                );
+                IdempotencyState::Started
+            }
+        }
+    }
+}
+
+impl Default for MemoryIdempotencyStore {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(24 * 60 * 60))
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    fn begin(&self, key: &str, fingerprint: u64) -> impl Future<Output = IdempotencyState> + Send {
+        std::future::ready(self.begin_at(key, fingerprint, Instant::now()))
+    }
+
+    fn complete(&self, key: &str, response: StoredResponse) -> impl Future<Output = ()> + Send {
+        if let Some(entry) = self.entries().map.get_mut(key) {
+            entry.response = Some(response);
+        }
+        std::future::ready(())
+    }
+
+    fn abort(&self, key: &str) -> impl Future<Output = ()> + Send {
+        self.entries().map.remove(key);
+        std::future::ready(())
+    }
+}
+
+/// Replays the stored response for requests carrying an already used `Idempotency-Key`.
+///
+/// The key is bound to a fingerprint of the method, URI and body of the first request. Reusing it
+/// for a different request is rejected with a 422 fail, retrying while the first request is still
+/// being processed with a 409 fail. Server errors are not stored, so they can be retried.
+/// Replayed responses carry an `Idempotent-Replayed: true` header. Requests with a safe method,
+/// such as `GET`, are passed through untouched.
+///
+/// Once the inner service produced a response, the key is never released. Responses too large to
+/// be buffered, or of unknown length, are streamed through and retries get a 410 fail instead of
+/// a replay. If the request is dropped before the inner service finished, the key is released.
+///
+/// Keys are global unless a [`scope`](Self::scope) is set: any client sending a known key with the
+/// same request gets the stored response, including headers such as `Set-Cookie`. Unscoped use is
+/// only safe for a single tenant. The fingerprint is not a cryptographic hash either, scoping also
+/// keeps forged collisions confined to the caller's own keys.
+pub struct IdempotencyLayer<St = MemoryIdempotencyStore> {
+    store: Arc<St>,
+    max_body: usize,
+    scope: Option<ScopeFn>,
+}
+
+type ScopeFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+impl<St> Clone for IdempotencyLayer<St> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            max_body: self.max_body,
+            scope: self.scope.clone(),
+        }
+    }
+}
+
+impl IdempotencyLayer {
+    /// Code of the fail returned while a request with the same key is in flight.
+    pub const IN_FLIGHT: u32 = 409;
+    /// Code of the fail returned when a key is reused for a different request.
+    pub const MISMATCH: u32 = 422;
+    /// Code of the fail returned on retries when the response could not be stored.
+    pub const NOT_REPLAYABLE: u32 = 410;
+
+    pub fn new() -> Self {
+        Self::with_store(MemoryIdempotencyStore::default())
+    }
+}
+
+impl Default for IdempotencyLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<St> IdempotencyLayer<St> {
+    pub fn with_store(store: St) -> Self {
+        Self {
+            store: Arc::new(store),
+            max_body: 2 * 1024 * 1024,
+            scope: None,
+        }
+    }
+
+    /// Scopes keys per caller, for example by user id, so callers can't replay each other's
+    /// responses. Requests `scope` returns `None` for are passed through without idempotency.
+    #[must_use]
+    pub fn scope<F>(mut self, scope: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.scope = Some(Arc::new(scope));
+        self
+    }
+
+    /// Scopes keys by the value of a header, such as `Authorization`. The value becomes part of
+    /// the stored key, prefer [`scope`](Self::scope) with a user id for stores outside the process.
+    #[must_use]
+    pub fn scope_header(self, name: HeaderName) -> Self {
+        self.scope(move |req| {
+            req.headers()
+                .get(&name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        })
+    }
+
+    /// Limits the request and response bodies buffered for fingerprinting and storage.
+    ///
+    /// Larger requests are rejected with a 413 fail, larger responses are not replayed.
+    #[must_use]
+    pub fn max_body(mut self, max_body: usize) -> Self {
+        self.max_body = max_body;
+        self
+    }
+}
+
+impl<S, St> Layer<S> for IdempotencyLayer<St> {
+    type Service = IdempotencyService<S, St>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IdempotencyService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+pub struct IdempotencyService<S, St = MemoryIdempotencyStore> {
+    inner: S,
+    layer: IdempotencyLayer<St>,
+}
+
+impl<S: Clone, St> Clone for IdempotencyService<S, St> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = stored.status;
+    *response.headers_mut() = stored.headers;
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// The stored response of a request whose actual response couldn't be buffered.
+async fn not_replayable() -> StoredResponse {
+    let (parts, body) = Brest::<()>::fail_code_status(
+        "The response for this idempotency key can't be replayed",
+        IdempotencyLayer::NOT_REPLAYABLE,
+        StatusCode::GONE,
+    )
+    .into_response()
+    .into_parts();
+    StoredResponse {
+        status: parts.status,
+        headers: parts.headers,
+        body: axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default(),
+    }
+}
+
+/// Releases a claimed key if the request is dropped before the inner service finished.
+struct Claim<St: IdempotencyStore> {
+    store: Arc<St>,
+    key: Option<String>,
+}
+
+impl<St: IdempotencyStore> Claim<St> {
+    fn new(store: Arc<St>, key: String) -> Self {
+        Self {
+            store,
+            key: Some(key),
+        }
+    }
+
+    /// Stops releasing the key on drop and returns it.
+    fn disarm(mut self) -> String {
+        self.key.take().unwrap_or_default()
+    }
+}
+
+impl<St: IdempotencyStore> Drop for Claim<St> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let abort = std::pin::pin!(self.store.abort(&key));
+            let _ = abort.poll(&mut Context::from_waker(Waker::noop()));
+        }
+    }
+}
+
+impl<S, St> Service<Request> for IdempotencyService<S, St>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send,
+    St: IdempotencyStore,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if req.method().is_safe() {
+            return Box::pin(self.inner.call(req));
+        }
+        let Some(mut key) = req
+            .headers()
+            .get(IDEMPOTENCY_KEY)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+        else {
+            return Box::pin(self.inner.call(req));
+        };
+        if let Some(scope) = &self.layer.scope {
+            let Some(scope) = scope(&req) else {
+                return Box::pin(self.inner.call(req));
+            };
+            // The length prefix keeps `("a:b", "c")` and `("a", "b:c")` apart.
+            key = format!("{}:{}:{}", scope.len(), scope, key);
+        }
+
+        // The inner service was polled ready, so take it and leave a clone behind.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = match axum::body::to_bytes(body, layer.max_body).await {
+                Ok(body) => body,
+                Err(_) => {
+                    return Ok(Brest::<()>::fail_status(
+                        "Request body too large",
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                    )
+                    .into_response())
+                }
+            };
+
+            let fingerprint = [
+                parts.method.as_str().as_bytes(),
+                parts.uri.to_string().as_bytes(),
+                &body,
+            ]
+            .iter()
+            .fold(FNV_OFFSET, |hash, bytes| fnv1a(fnv1a(hash, bytes), &[0]));
+
+            match layer.store.begin(&key, fingerprint).await {
+                IdempotencyState::Started => {}
+                IdempotencyState::InFlight => {
+                    return Ok(Brest::<()>::fail_code_status(
+                        "A request with this idempotency key is still being processed",
+                        IdempotencyLayer::IN_FLIGHT,
+                        StatusCode::CONFLICT,
+                    )
+                    .into_response())
+                }
+                IdempotencyState::Mismatch => {
+                    return Ok(Brest::<()>::fail_code_status(
+                        "This idempotency key was used for a different request",
+                        IdempotencyLayer::MISMATCH,
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                    )
+                    .into_response())
+                }
+                IdempotencyState::Completed(stored) => return Ok(replay(stored)),
+            }
+
+            let claim = Claim::new(layer.store.clone(), key);
+            let result = inner.call(Request::from_parts(parts, Body::from(body))).await;
+            let key = claim.disarm();
+            let response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    layer.store.abort(&key).await;
+                    return Err(e);
+                }
+            };
+            if response.status().is_server_error() {
+                layer.store.abort(&key).await;
+                return Ok(response);
+            }
+
+            let (parts, body) = response.into_parts();
+            if body
+                .size_hint()
+                .upper()
+                .is_none_or(|size| size > layer.max_body as u64)
+            {
+                layer.store.complete(&key, not_replayable().await).await;
+                return Ok(Response::from_parts(parts, body));
+            }
+            let body = match axum::body::to_bytes(body, layer.max_body).await {
+                Ok(body) => body,
+                Err(e) => {
+                    layer.store.complete(&key, not_replayable().await).await;
+                    return Ok(Brest::<()>::error(e).into_response());
+                }
+            };
+            let stored = StoredResponse {
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+            };
+            layer.store.complete(&key, stored).await;
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    fn request(key: &str, body: &'static str) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri("/pay")
+            .header(IDEMPOTENCY_KEY, key)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replays_response() {
+        let counter = Arc::new(AtomicU32::new(0));
+        let app = Router::new()
+            .route(
+                "/pay",
+                post({
+                    let counter = counter.clone();
+                    move || async move {
+                        let n = counter.fetch_add(1, Ordering::SeqCst);
+                        Brest::<u32>::created(n, "/payments/1")
+                    }
+                }),
+            )
+            .layer(IdempotencyLayer::new());
+
+        let first = app.clone().oneshot(request("k", "{}")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert_eq!(body(first).await, r#"{"type":"success","data":0}"#);
+
+        let second = app.clone().oneshot(request("k", "{}")).await.unwrap();
+        assert_eq!(second.status(), StatusCode::CREATED);
+        assert_eq!(second.headers()[IDEMPOTENT_REPLAYED], "true");
+        assert_eq!(second.headers()["location"], "/payments/1");
+        assert_eq!(body(second).await, r#"{"type":"success","data":0}"#);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        let mismatch = app.clone().oneshot(request("k", r#"{"other":1}"#)).await.unwrap();
+        assert_eq!(mismatch.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let other = app.oneshot(request("other", "{}")).await.unwrap();
+        assert_eq!(body(other).await, r#"{"type":"success","data":1}"#);
+    }
+
+    #[tokio::test]
+    async fn test_scoped_keys() {
+        let counter = Arc::new(AtomicU32::new(0));
+        let app = Router::new()
+            .route(
+                "/pay",
+                post({
+                    let counter = counter.clone();
+                    move || async move { Brest::<u32>::success(counter.fetch_add(1, Ordering::SeqCst)) }
+                }),
+            )
+            .layer(IdempotencyLayer::new().scope_header(axum::http::header::AUTHORIZATION));
+        let scoped = |user: &str| {
+            let mut req = request("k", "{}");
+            req.headers_mut()
+                .insert(axum::http::header::AUTHORIZATION, HeaderValue::from_str(user).unwrap());
+            req
+        };
+
+        let alice = app.clone().oneshot(scoped("alice")).await.unwrap();
+        assert_eq!(body(alice).await, r#"{"type":"success","data":0}"#);
+        let bob = app.clone().oneshot(scoped("bob")).await.unwrap();
+        assert!(bob.headers().get(IDEMPOTENT_REPLAYED).is_none());
+        assert_eq!(body(bob).await, r#"{"type":"success","data":1}"#);
+
+        let alice = app.clone().oneshot(scoped("alice")).await.unwrap();
+        assert_eq!(alice.headers()[IDEMPOTENT_REPLAYED], "true");
+        assert_eq!(body(alice).await, r#"{"type":"success","data":0}"#);
+
+        let anonymous = app.oneshot(request("k", "{}")).await.unwrap();
+        assert_eq!(body(anonymous).await, r#"{"type":"success","data":2}"#);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        let store = MemoryIdempotencyStore::default();
+        assert_eq!(store.begin("k", 1).await, IdempotencyState::Started);
+        assert_eq!(store.begin("k", 1).await, IdempotencyState::InFlight);
+        assert_eq!(store.begin("k", 2).await, IdempotencyState::Mismatch);
+        store.abort("k").await;
+        assert_eq!(store.begin("k", 2).await, IdempotencyState::Started);
+
+        let stored = StoredResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(b"{}"),
+        };
+        store.complete("k", stored.clone()).await;
+        assert_eq!(store.begin("k", 2).await, IdempotencyState::Completed(stored));
+    }
+
+    #[test]
+    fn test_memory_store_expires_amortized() {
+        let store = MemoryIdempotencyStore::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let later = start + Duration::from_secs(60);
+        store.begin_at("k", 1, start);
+        assert_eq!(store.begin_at("k", 2, start), IdempotencyState::Mismatch);
+        // Expired keys can be claimed again before they are pruned.
+        assert_eq!(store.begin_at("k", 2, later), IdempotencyState::Started);
+
+        for i in 0..MemoryIdempotencyStore::PRUNE_THRESHOLD {
+            store.begin_at(&i.to_string(), 1, start);
+        }
+        store.begin_at("new", 1, later);
+        let entries = store.entries();
+        assert_eq!(entries.map.len(), 2);
+        assert_eq!(entries.prune_at, MemoryIdempotencyStore::PRUNE_THRESHOLD);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight() {
+        let notify = Arc::new(Notify::new());
+        let app = Router::new()
+            .route(
+                "/pay",
+                post({
+                    let notify = notify.clone();
+                    move || async move {
+                        notify.notified().await;
+                        Brest::<()>::success(())
+                    }
+                }),
+            )
+            .layer(IdempotencyLayer::new());
+
+        let first = app.clone().oneshot(request("k", ""));
+        let second = async {
+            let response = app.clone().oneshot(request("k", "")).await.unwrap();
+            notify.notify_one();
+            response
+        };
+        let (first, second) = tokio::join!(first, second);
+
+        assert_eq!(first.unwrap().status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            body(second).await,
+            r#"{"type":"fail","message":"A request with this idempotency key is still being processed","code":409}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_response_not_rerun() {
+        let counter = Arc::new(AtomicU32::new(0));
+        let app = Router::new()
+            .route(
+                "/pay",
+                post({
+                    let counter = counter.clone();
+                    move || async move { Brest::<u32>::success(counter.fetch_add(1, Ordering::SeqCst)) }
+                }),
+            )
+            .layer(IdempotencyLayer::new().max_body(8));
+
+        let first = app.clone().oneshot(request("k", "")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(body(first).await, r#"{"type":"success","data":0}"#);
+
+        let second = app.oneshot(request("k", "")).await.unwrap();
+        assert_eq!(second.status(), StatusCode::GONE);
+        assert_eq!(
+            body(second).await,
+            r#"{"type":"fail","message":"The response for this idempotency key can't be replayed","code":410}"#
+        );
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_request_releases_key() {
+        let counter = Arc::new(AtomicU32::new(0));
+        let app = Router::new()
+            .route(
+                "/pay",
+                post({
+                    let counter = counter.clone();
+                    move || async move {
+                        if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                            std::future::pending::<()>().await;
+                        }
+                        Brest::<()>::success(())
+                    }
+                }),
+            )
+            .layer(IdempotencyLayer::new());
+
+        let mut first = Box::pin(app.clone().oneshot(request("k", "")));
+        let poll = first.as_mut().poll(&mut Context::from_waker(Waker::noop()));
+        assert!(poll.is_pending());
+        drop(first);
+
+        let second = app.oneshot(request("k", "")).await.unwrap();
+        assert_eq!(second.status(), StatusCode::OK);
+        assert!(second.headers().get(IDEMPOTENT_REPLAYED).is_none());
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_safe_methods_pass_through() {
+        let counter = Arc::new(AtomicU32::new(0));
+        let app = Router::new()
+            .route(
+                "/pay",
+                get({
+                    let counter = counter.clone();
+                    move || async move { Brest::<u32>::success(counter.fetch_add(1, Ordering::SeqCst)) }
+                }),
+            )
+            .layer(IdempotencyLayer::new());
+
+        for n in 0..2 {
+            let req = Request::builder()
+                .uri("/pay")
+                .header(IDEMPOTENCY_KEY, "k")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(req).await.unwrap();
+            assert!(response.headers().get(IDEMPOTENT_REPLAYED).is_none());
+            assert_eq!(body(response).await, format!(r#"{{"type":"success","data":{n}}}"#));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_not_stored() {
+        let app = Router::new()
+            .route("/pay", post(|| async { Brest::<()>::error("down") }))
+            .layer(IdempotencyLayer::new());
+
+        let first = app.clone().oneshot(request("k", "")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let second = app.oneshot(request("k", "")).await.unwrap();
+        assert!(second.headers().get(IDEMPOTENT_REPLAYED).is_none());
+    }
+}
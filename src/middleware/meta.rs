@@ -0,0 +1,150 @@
+use std::task::{Context, Poll};
+
+use axum::extract::Request;
+use axum::http::response::Parts;
+use axum::response::Response;
+use serde_json::{Map, Value};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::{merge_meta, BoxFuture};
+
+/// Fills the `meta` object of Brest envelopes after the handler has run.
+///
+/// The closure receives the response parts (status, headers, extensions) and returns the
+/// entries to merge into `meta`. Entries already set by the handler are kept.
+#[derive(Clone)]
+pub struct MetaLayer<F> {
+    f: F,
+}
+
+impl<F> MetaLayer<F>
+where
+    F: Fn(&Parts) -> Map<String, Value>,
+{
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<S, F: Clone> Layer<S> for MetaLayer<F> {
+    type Service = MetaService<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetaService {
+            inner,
+            f: self.f.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetaService<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F> Service<Request> for MetaService<S, F>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+    F: Fn(&Parts) -> Map<String, Value> + Clone + Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let future = self.inner.call(req);
+        let f = self.f.clone();
+        Box::pin(async move {
+            let (parts, body) = future.await?.into_parts();
+            let meta = f(&parts);
+            Ok(merge_meta(Response::from_parts(parts, body), meta).await)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::{body, body_json};
+    use crate::Brest;
+    use axum::body::Body;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn meta(_: &Parts) -> Map<String, Value> {
+        let mut meta = Map::new();
+        meta.insert("version".to_string(), json!("v1"));
+        meta.insert("request_id".to_string(), json!("layer"));
+        meta
+    }
+
+    #[tokio::test]
+    async fn test_meta_layer_fills_meta() {
+        let app = Router::new()
+            .route("/", get(|| async { Brest::<u32>::success(1) }))
+            .layer(MetaLayer::new(meta));
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(
+            body_json(response).await,
+            json!({"type": "success", "data": 1, "meta": {"version": "v1", "request_id": "layer"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_meta_layer_keeps_handler_meta() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Brest::<(), u32, Value>::fail("nope").with_meta(json!({"request_id": "handler"}))
+                }),
+            )
+            .layer(MetaLayer::new(meta));
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(
+            body_json(response).await,
+            json!({"type": "fail", "message": "nope", "meta": {"version": "v1", "request_id": "handler"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_meta_layer_keeps_key_order() {
+        #[derive(serde::Serialize)]
+        struct Item {
+            z: u32,
+            a: u32,
+        }
+
+        let app = Router::new()
+            .route("/", get(|| async { Brest::<Item>::success(Item { z: 1, a: 2 }) }))
+            .layer(MetaLayer::new(meta));
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(
+            body(response).await,
+            r#"{"type":"success","data":{"z":1,"a":2},"meta":{"request_id":"layer","version":"v1"}}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_meta_layer_ignores_other_bodies() {
+        let app = Router::new()
+            .route("/", get(|| async { "plain".into_response() }))
+            .layer(MetaLayer::new(meta));
+
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(body(response).await, "plain");
+    }
+}
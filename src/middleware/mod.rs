@@ -0,0 +1,193 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use axum::body::Body;
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
+use serde_json::{Map, Value};
+
+use crate::Brest;
+
+mod catch_panic;
+mod conditional;
+mod envelope;
+#[cfg(feature = "tower")]
+mod handle_error;
+mod idempotency;
+mod meta;
+mod rate_limit;
+mod request_id;
+
+pub use catch_panic::{CatchPanicLayer, CatchPanicService};
+pub use conditional::{compute_etag, ConditionalGetLayer, ConditionalGetService};
+pub use envelope::{EnvelopeLayer, EnvelopeService};
+#[cfg(feature = "tower")]
+pub use handle_error::{handle_tower_error, TowerErrorHandler};
+pub use idempotency::{
+    IdempotencyLayer, IdempotencyService, IdempotencyState, IdempotencyStore,
+    MemoryIdempotencyStore, StoredResponse, IDEMPOTENCY_KEY,
+};
+pub use meta::{MetaLayer, MetaService};
+pub use rate_limit::{
+    MemoryRateLimitStore, Quota, RateLimitKey, RateLimitLayer, RateLimitService, RateLimitStatus,
+    RateLimitStore,
+};
+pub use request_id::{generate_request_id, RequestId, RequestIdLayer, RequestIdService, X_REQUEST_ID};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 64 bit FNV-1a, stable across processes unlike `DefaultHasher`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+pub(crate) fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+pub(crate) fn is_envelope(value: &Value) -> bool {
+    matches!(
+        value.get("type").and_then(Value::as_str),
+        Some("success" | "error" | "fail")
+    )
+}
+
+/// Reads the body of a response in tests.
+#[cfg(test)]
+pub(crate) async fn body(response: Response) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Reads the body of a response as JSON in tests.
+#[cfg(test)]
+pub(crate) async fn body_json(response: Response) -> Value {
+    serde_json::from_str(&body(response).await).unwrap()
+}
+
+/// The entries of a JSON object in their original order, with the values kept verbatim.
+struct Entries(Vec<(String, Box<RawValue>)>);
+
+impl<'de> Deserialize<'de> for Entries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = Entries;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Entries, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+impl Serialize for Entries {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl Entries {
+    fn get(&self, key: &str) -> Option<&RawValue> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, value)| &**value)
+    }
+}
+
+/// Adds entries to the `meta` object of a Brest JSON response, keeping those already present.
+///
+/// `f` receives the `type` of the envelope and returns the entries to add. The body is only
+/// rewritten when something was added, and then keeps its key order. Responses that are not JSON
+/// or whose body is not a Brest envelope are returned unchanged.
+pub(crate) async fn extend_meta<F>(response: Response, f: F) -> Response
+where
+    F: FnOnce(&str) -> Map<String, Value>,
+{
+    if !is_json(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => return Brest::<()>::error(e).into_response(),
+    };
+
+    let Some(rewritten) = add_meta(&bytes, f) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(rewritten.len()));
+    Response::from_parts(parts, Body::from(rewritten))
+}
+
+/// The envelope in `bytes` with the entries returned by `f` added to its `meta`, `None` if
+/// there is nothing to change.
+fn add_meta<F>(bytes: &[u8], f: F) -> Option<Vec<u8>>
+where
+    F: FnOnce(&str) -> Map<String, Value>,
+{
+    let mut envelope = serde_json::from_slice::<Entries>(bytes).ok()?;
+    let kind = serde_json::from_str::<&str>(envelope.get("type")?.get()).ok()?;
+    if !matches!(kind, "success" | "error" | "fail") {
+        return None;
+    }
+
+    let mut meta = match envelope.get("meta").map(|meta| meta.get()) {
+        None | Some("null") => Entries(Vec::new()),
+        Some(meta) => serde_json::from_str::<Entries>(meta).ok()?,
+    };
+    let mut changed = false;
+    for (key, value) in f(kind) {
+        if meta.get(&key).is_none() {
+            meta.0.push((key, serde_json::value::to_raw_value(&value).ok()?));
+            changed = true;
+        }
+    }
+    if !changed {
+        return None;
+    }
+
+    let meta = serde_json::value::to_raw_value(&meta).ok()?;
+    match envelope.0.iter_mut().find(|(key, _)| key == "meta") {
+        Some((_, value)) => *value = meta,
+        None => envelope.0.push(("meta".to_string(), meta)),
+    }
+    serde_json::to_vec(&envelope).ok()
+}
+
+/// Merges `meta` into the `meta` object of a Brest JSON response.
+///
+/// Keys already present in the envelope are left untouched, so values set by the handler win.
+pub async fn merge_meta(response: Response, meta: Map<String, Value>) -> Response {
+    if meta.is_empty() {
+        return response;
+    }
+
+    extend_meta(response, |_| meta).await
+}
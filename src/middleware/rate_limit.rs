@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::BoxFuture;
+use crate::Brest;
+
+/// Allows `limit` requests per `period`, refilled continuously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub fn new(limit: u32, period: Duration) -> Self {
+        Self { limit, period }
+    }
+
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+}
+
+/// The outcome of a [`RateLimitStore::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the quota is fully replenished.
+    pub reset: Duration,
+    /// Time until the next request would be allowed, if this one was rejected.
+    pub retry_after: Option<Duration>,
+}
+
+/// Keeps track of the requests made per key.
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Records a request for `key` and reports whether it fits in `quota`.
+    fn check(&self, key: &str, quota: Quota) -> impl Future<Output = RateLimitStatus> + Send;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    prune_at: usize,
+}
+
+/// An in-memory token bucket store, local to the process.
+#[derive(Debug)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<Buckets>,
+}
+
+impl MemoryRateLimitStore {
+    const PRUNE_THRESHOLD: usize = 10_000;
+
+    pub fn new() -> Self {
+        Self {
+            buckets: Mutex::new(Buckets {
+                map: HashMap::new(),
+                prune_at: Self::PRUNE_THRESHOLD,
+            }),
+        }
+    }
+
+    fn check_at(&self, key: &str, quota: Quota, now: Instant) -> RateLimitStatus {
+        // An empty quota never refills, every request is rejected.
+        if quota.limit == 0 {
+            return RateLimitStatus {
+                allowed: false,
+                limit: 0,
+                remaining: 0,
+                reset: Duration::ZERO,
+                retry_after: None,
+            };
+        }
+
+        let limit = f64::from(quota.limit);
+        let rate = limit / quota.period.as_secs_f64().max(f64::EPSILON);
+        let refill = |bucket: &Bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            (bucket.tokens + elapsed * rate).min(limit)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.map.len() > buckets.prune_at {
+            buckets.map.retain(|_, bucket| refill(bucket) < limit);
+            // Prune again once the map doubled, so the scan stays amortized over the inserts.
+            buckets.prune_at = (buckets.map.len() * 2).max(Self::PRUNE_THRESHOLD);
+        }
+
+        let bucket = buckets.map.entry(key.to_string()).or_insert(Bucket {
+            tokens: limit,
+            updated: now,
+        });
+        let mut tokens = refill(bucket);
+        let allowed = tokens >= 1.0;
+        if allowed {
+            tokens -= 1.0;
+        }
+        *bucket = Bucket {
+            tokens,
+            updated: now,
+        };
+
+        RateLimitStatus {
+            allowed,
+            limit: quota.limit,
+            remaining: tokens.floor() as u32,
+            reset: Duration::from_secs_f64((limit - tokens) / rate),
+            retry_after: (!allowed).then(|| Duration::from_secs_f64((1.0 - tokens) / rate)),
+        }
+    }
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    fn check(&self, key: &str, quota: Quota) -> impl Future<Output = RateLimitStatus> + Send {
+        std::future::ready(self.check_at(key, quota, Instant::now()))
+    }
+}
+
+type KeyFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+/// How requests are grouped into rate limit buckets.
+#[derive(Clone)]
+pub enum RateLimitKey {
+    /// The client address recorded by the `proxies` trusted proxies in front of the service,
+    /// which each append to `X-Forwarded-For`: the entry `proxies` places from the right, so
+    /// values sent by the client itself are skipped. Without that entry, `X-Real-Ip` is used.
+    /// Requests carrying neither share one bucket, so omitting them doesn't bypass the limit.
+    ///
+    /// Only use this behind proxies that set these headers, clients can send them as well.
+    ForwardedFor { proxies: usize },
+    /// The peer address from axum's [`ConnectInfo`](axum::extract::ConnectInfo), which requires
+    /// serving the app with `into_make_service_with_connect_info::<SocketAddr>()`. Requests
+    /// without it share one bucket.
+    #[cfg(feature = "connect-info")]
+    ConnectInfo,
+    /// The value of a header, such as an API key. Requests without it are not limited.
+    Header(HeaderName),
+    /// A key computed from the request. Requests it returns `None` for are not limited.
+    Custom(KeyFn),
+}
+
+impl RateLimitKey {
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(f))
+    }
+
+    fn extract(&self, req: &Request) -> Option<String> {
+        match self {
+            Self::ForwardedFor { proxies } => {
+                let forwarded_for = req
+                    .headers()
+                    .get_all("x-forwarded-for")
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .flat_map(|value| value.split(','))
+                    .collect::<Vec<_>>();
+                let forwarded_for = forwarded_for
+                    .iter()
+                    .rev()
+                    .nth(proxies.saturating_sub(1))
+                    .copied();
+                let real_ip = || {
+                    req.headers()
+                        .get("x-real-ip")
+                        .and_then(|value| value.to_str().ok())
+                };
+                let ip = forwarded_for
+                    .or_else(real_ip)
+                    .map(str::trim)
+                    .filter(|ip| !ip.is_empty())
+                    .unwrap_or("unknown");
+                Some(ip.to_string())
+            }
+            #[cfg(feature = "connect-info")]
+            Self::ConnectInfo => {
+                let ip = req
+                    .extensions()
+                    .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+                    .map(|info| info.0.ip().to_string());
+                Some(ip.unwrap_or_else(|| "unknown".to_string()))
+            }
+            Self::Header(name) => req
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            Self::Custom(f) => f(req),
+        }
+    }
+}
+
+/// Rejects requests exceeding a [`Quota`] with a Brest fail and status 429.
+///
+/// All limited responses carry `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset`
+/// headers, rejections additionally carry `Retry-After`.
+pub struct RateLimitLayer<St = MemoryRateLimitStore, C = u32> {
+    quota: Quota,
+    key: RateLimitKey,
+    store: Arc<St>,
+    code: Option<C>,
+}
+
+impl<St, C: Clone> Clone for RateLimitLayer<St, C> {
+    fn clone(&self) -> Self {
+        Self {
+            quota: self.quota,
+            key: self.key.clone(),
+            store: self.store.clone(),
+            code: self.code.clone(),
+        }
+    }
+}
+
+impl<C> RateLimitLayer<MemoryRateLimitStore, C> {
+    /// Limits requests grouped by `key` using a [`MemoryRateLimitStore`].
+    pub fn new(quota: Quota, key: RateLimitKey) -> Self {
+        Self {
+            quota,
+            key,
+            store: Arc::new(MemoryRateLimitStore::new()),
+            code: None,
+        }
+    }
+}
+
+impl<St, C> RateLimitLayer<St, C> {
+    #[must_use]
+    pub fn key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn store<T: RateLimitStore>(self, store: T) -> RateLimitLayer<T, C> {
+        RateLimitLayer {
+            quota: self.quota,
+            key: self.key,
+            store: Arc::new(store),
+            code: self.code,
+        }
+    }
+
+    #[must_use]
+    pub fn code(mut self, code: C) -> Self {
+        self.code = Some(code);
+        self
+    }
+}
+
+impl<S, St, C: Clone> Layer<S> for RateLimitLayer<St, C> {
+    type Service = RateLimitService<S, St, C>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+pub struct RateLimitService<S, St = MemoryRateLimitStore, C = u32> {
+    inner: S,
+    layer: RateLimitLayer<St, C>,
+}
+
+impl<S: Clone, St, C: Clone> Clone for RateLimitService<S, St, C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+fn set_headers(headers: &mut HeaderMap, status: &RateLimitStatus) {
+    headers.insert(
+        HeaderName::from_static("ratelimit-limit"),
+        HeaderValue::from(status.limit),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-remaining"),
+        HeaderValue::from(status.remaining),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-reset"),
+        HeaderValue::from(ceil_secs(status.reset)),
+    );
+    if let Some(retry_after) = status.retry_after {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(ceil_secs(retry_after)));
+    }
+}
+
+impl<S, St, C> Service<Request> for RateLimitService<S, St, C>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    St: RateLimitStore,
+    C: Serialize + Clone + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let Some(key) = self.layer.key.extract(&req) else {
+            return Box::pin(self.inner.call(req));
+        };
+
+        // The inner service was polled ready, so take it and leave a clone behind.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let status = layer.store.check(&key, layer.quota).await;
+            let mut response = if status.allowed {
+                inner.call(req).await?
+            } else {
+                let message = "Too many requests";
+                match layer.code {
+                    Some(code) => Brest::<(), C>::fail_code_status(
+                        message,
+                        code,
+                        StatusCode::TOO_MANY_REQUESTS,
+                    ),
+                    None => Brest::<(), C>::fail_status(message, StatusCode::TOO_MANY_REQUESTS),
+                }
+                .into_response()
+            };
+            set_headers(response.headers_mut(), &status);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body;
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_memory_store_refills() {
+        let store = MemoryRateLimitStore::new();
+        let quota = Quota::new(2, Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert!(store.check_at("a", quota, start).allowed);
+        let status = store.check_at("a", quota, start);
+        assert!(status.allowed);
+        assert_eq!(status.remaining, 0);
+
+        let status = store.check_at("a", quota, start);
+        assert!(!status.allowed);
+        assert_eq!(status.retry_after, Some(Duration::from_secs(5)));
+        assert!(store.check_at("b", quota, start).allowed);
+
+        assert!(store.check_at("a", quota, start + Duration::from_secs(5)).allowed);
+    }
+
+    #[test]
+    fn test_memory_store_empty_quota() {
+        let store = MemoryRateLimitStore::new();
+        let status = store.check_at("a", Quota::per_minute(0), Instant::now());
+        assert!(!status.allowed);
+        assert_eq!(status.retry_after, None);
+    }
+
+    #[test]
+    fn test_memory_store_prunes_amortized() {
+        let store = MemoryRateLimitStore::new();
+        let quota = Quota::per_minute(2);
+        let start = Instant::now();
+        for i in 0..=MemoryRateLimitStore::PRUNE_THRESHOLD {
+            store.check_at(&i.to_string(), quota, start);
+        }
+        // Once refilled, every bucket is dropped by the next prune.
+        let later = start + Duration::from_secs(60);
+        store.check_at("new", quota, later);
+        let buckets = store.buckets.lock().unwrap();
+        assert_eq!(buckets.map.len(), 1);
+        assert_eq!(buckets.prune_at, MemoryRateLimitStore::PRUNE_THRESHOLD);
+        drop(buckets);
+
+        for i in 0..=MemoryRateLimitStore::PRUNE_THRESHOLD {
+            store.check_at(&i.to_string(), quota, later);
+        }
+        // Nothing was refilled, the next prune is pushed back.
+        assert_eq!(store.buckets.lock().unwrap().prune_at, 2 * (MemoryRateLimitStore::PRUNE_THRESHOLD + 1));
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(|| async { Brest::<()>::success(()) }))
+            .layer(
+                RateLimitLayer::<MemoryRateLimitStore, u32>::new(
+                    Quota::per_minute(1),
+                    RateLimitKey::Header(HeaderName::from_static("x-api-key")),
+                )
+                .code(429),
+            )
+    }
+
+    fn request(key: Option<&str>) -> Request {
+        let mut req = Request::builder();
+        if let Some(key) = key {
+            req = req.header("x-api-key", key);
+        }
+        req.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_forwarded_for_key() {
+        let one = RateLimitKey::ForwardedFor { proxies: 1 };
+        let two = RateLimitKey::ForwardedFor { proxies: 2 };
+        let req = Request::builder()
+            .header("x-forwarded-for", "198.51.100.7, 203.0.113.1")
+            .header("x-forwarded-for", "10.0.0.1")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(one.extract(&req).as_deref(), Some("10.0.0.1"));
+        assert_eq!(two.extract(&req).as_deref(), Some("203.0.113.1"));
+
+        let req = Request::builder()
+            .header("x-real-ip", "203.0.113.2")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(one.extract(&req).as_deref(), Some("203.0.113.2"));
+
+        assert_eq!(one.extract(&request(None)).as_deref(), Some("unknown"));
+    }
+
+    #[cfg(feature = "connect-info")]
+    #[test]
+    fn test_connect_info_key() {
+        use axum::extract::ConnectInfo;
+        use std::net::SocketAddr;
+
+        let mut req = request(None);
+        assert_eq!(RateLimitKey::ConnectInfo.extract(&req).as_deref(), Some("unknown"));
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([203, 0, 113, 3], 4711))));
+        assert_eq!(RateLimitKey::ConnectInfo.extract(&req).as_deref(), Some("203.0.113.3"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_layer() {
+        let app = app();
+
+        let response = app.clone().oneshot(request(Some("k"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["ratelimit-limit"], "1");
+        assert_eq!(response.headers()["ratelimit-remaining"], "0");
+
+        let response = app.clone().oneshot(request(Some("k"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+        assert_eq!(body(response).await, r#"{"type":"fail","message":"Too many requests","code":429}"#);
+
+        let response = app.clone().oneshot(request(Some("other"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("ratelimit-limit").is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum::response::Response;
+use serde_json::{Map, Value};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::{extend_meta, BoxFuture};
+use crate::Brest;
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The id of the current request, set by [`RequestIdLayer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId {
+    pub id: String,
+    /// The trace id from a W3C `traceparent` header, if one was sent.
+    pub trace_id: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = Brest;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<RequestId>().cloned().ok_or_else(|| {
+            Brest::error_status(
+                "Missing request id, is `RequestIdLayer` installed?",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })
+    }
+}
+
+/// Generates a random 128 bit id, hex encoded.
+pub fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    let mut id = String::with_capacity(32);
+    for _ in 0..2 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u64(count);
+        id.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    id
+}
+
+/// Whether a client supplied id is short and plain enough to be echoed into headers and bodies.
+fn valid_request_id(id: &str) -> bool {
+    (1..=MAX_REQUEST_ID_LEN).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+fn parse_trace_id(traceparent: &str) -> Option<String> {
+    let mut fields = traceparent.trim().split('-');
+    let _version = fields.next().filter(|v| v.len() == 2)?;
+    let trace_id = fields.next()?;
+    let valid = trace_id.len() == 32
+        && trace_id.bytes().all(|b| b.is_ascii_hexdigit())
+        && trace_id.bytes().any(|b| b != b'0');
+    valid.then(|| trace_id.to_ascii_lowercase())
+}
+
+/// Reads or generates a request id, echoes it in the response headers and stamps it into the
+/// `meta` object of Brest error and fail bodies.
+///
+/// Ids sent by the client are only kept if they're at most 128 ASCII alphanumerics, `-`, `_`,
+/// `.` or `:`, a new one is generated otherwise.
+///
+/// The id is available to handlers through the [`RequestId`] extractor.
+#[derive(Clone)]
+pub struct RequestIdLayer {
+    header: HeaderName,
+    generate: Arc<dyn Fn() -> String + Send + Sync>,
+}
+
+impl RequestIdLayer {
+    pub fn new() -> Self {
+        Self {
+            header: X_REQUEST_ID,
+            generate: Arc::new(generate_request_id),
+        }
+    }
+
+    /// Uses `header` instead of `x-request-id`.
+    #[must_use]
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Uses `generate` to create ids for requests that don't carry one.
+    #[must_use]
+    pub fn generator<F>(mut self, generate: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.generate = Arc::new(generate);
+        self
+    }
+}
+
+impl Default for RequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+    layer: RequestIdLayer,
+}
+
+impl<S> Service<Request> for RequestIdService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let id = req
+            .headers()
+            .get(&self.layer.header)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| valid_request_id(value))
+            .map(str::to_string)
+            .unwrap_or_else(|| (self.layer.generate)());
+        let trace_id = req
+            .headers()
+            .get(TRACEPARENT)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_trace_id);
+        let request_id = RequestId { id, trace_id };
+        req.extensions_mut().insert(request_id.clone());
+
+        let header = self.layer.header.clone();
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let mut response = future.await?;
+            if let Ok(value) = HeaderValue::from_str(&request_id.id) {
+                response.headers_mut().insert(header, value);
+            }
+            response.extensions_mut().insert(request_id.clone());
+            Ok(stamp(response, &request_id).await)
+        })
+    }
+}
+
+async fn stamp(response: Response, request_id: &RequestId) -> Response {
+    if response.status().is_success() {
+        return response;
+    }
+
+    extend_meta(response, |kind| {
+        let mut meta = Map::new();
+        if kind == "success" {
+            return meta;
+        }
+        meta.insert("request_id".to_string(), Value::String(request_id.id.clone()));
+        if let Some(trace_id) = &request_id.trace_id {
+            meta.insert("trace_id".to_string(), Value::String(trace_id.clone()));
+        }
+        meta
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::Json;
+    use crate::middleware::body_json;
+    use axum::body::Body;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/id", get(|id: RequestId| async move { Brest::<String>::success(id.id) }))
+            .route("/fail", get(|| async { Brest::<()>::fail("nope") }))
+            .route("/json", post(|Json(v): Json<u32>| async move { Brest::<u32>::success(v) }))
+            .layer(RequestIdLayer::new().generator(|| "generated".to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated() {
+        let response = app()
+            .oneshot(Request::builder().uri("/id").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[X_REQUEST_ID], "generated");
+        assert_eq!(body_json(response).await, json!({"type": "success", "data": "generated"}));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_stamped_into_fail() {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .uri("/fail")
+                    .header(X_REQUEST_ID, "abc")
+                    .header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[X_REQUEST_ID], "abc");
+        assert_eq!(
+            body_json(response).await,
+            json!({
+                "type": "fail",
+                "message": "nope",
+                "meta": {"request_id": "abc", "trace_id": "4bf92f3577b34da6a3ce929d0e0e4736"}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_stamped_into_rejection() {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = body_json(response).await;
+        assert_eq!(body["type"], "fail");
+        assert_eq!(body["meta"]["request_id"], "generated");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_id_replaced() {
+        for id in ["", "a b", "<script>", &"a".repeat(129)] {
+            let response = app()
+                .oneshot(Request::builder().uri("/id").header(X_REQUEST_ID, id).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.headers()[X_REQUEST_ID], "generated");
+        }
+        assert!(valid_request_id("req-1_a.b:c"));
+    }
+
+    #[tokio::test]
+    async fn test_stamped_fail_parses() {
+        let response = app()
+            .oneshot(Request::builder().uri("/fail").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let brest = Brest::<()>::from_response(axum::http::Response::from_parts(parts, bytes)).unwrap();
+        assert_eq!(brest.message(), Some("nope"));
+    }
+
+    #[test]
+    fn test_parse_trace_id() {
+        assert_eq!(
+            parse_trace_id("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        assert_eq!(parse_trace_id("00-00000000000000000000000000000000-00f067aa0ba902b7-01"), None);
+        assert_eq!(parse_trace_id("garbage"), None);
+    }
+
+    #[test]
+    fn test_generate_request_id() {
+        let a = generate_request_id();
+        let b = generate_request_id();
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,302 @@
+//! [`utoipa`] integration: schemas and responses for [`Brest`] envelopes, and the fail
+//! responses produced by the extractors in [`crate::extractors`].
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use utoipa::openapi::response::{Response, ResponseBuilder};
+use utoipa::openapi::schema::{Discriminator, ObjectBuilder, OneOfBuilder, Schema, Type};
+use utoipa::openapi::{Content, RefOr};
+use utoipa::{IntoResponses, PartialSchema, ToSchema};
+
+use crate::debug::DebugInfo;
+use crate::Brest;
+
+const JSON: &str = "application/json";
+
+fn tag(kind: &'static str) -> ObjectBuilder {
+    ObjectBuilder::new()
+        .title(Some(kind))
+        .property(
+            "type",
+            ObjectBuilder::new()
+                .schema_type(Type::String)
+                .enum_values(Some([kind])),
+        )
+        .required("type")
+}
+
+/// Schema of the `success` variant of a `Brest<D, _, Meta>`.
+pub fn success_schema<D: ToSchema, Meta: ToSchema>() -> Schema {
+    tag("success")
+        .property("data", D::schema())
+        .required("data")
+        .property("meta", Meta::schema())
+        .into()
+}
+
+fn failure_schema<C: ToSchema, Meta: ToSchema>(kind: &'static str) -> Schema {
+    tag(kind)
+        .property("message", String::schema())
+        .required("message")
+        .property("code", C::schema())
+        .property("debug", DebugInfo::schema())
+        .property("meta", Meta::schema())
+        .into()
+}
+
+/// Schema of the `error` variant of a `Brest<_, C, Meta>`.
+pub fn error_schema<C: ToSchema, Meta: ToSchema>() -> Schema {
+    failure_schema::<C, Meta>("error")
+}
+
+/// Schema of the `fail` variant of a `Brest<_, C, Meta>`.
+pub fn fail_schema<C: ToSchema, Meta: ToSchema>() -> Schema {
+    failure_schema::<C, Meta>("fail")
+}
+
+impl<D, C, Meta> PartialSchema for Brest<D, C, Meta>
+where
+    D: Serialize + ToSchema,
+    C: ToSchema,
+    Meta: ToSchema,
+{
+    fn schema() -> RefOr<Schema> {
+        OneOfBuilder::new()
+            .item(success_schema::<D, Meta>())
+            .item(error_schema::<C, Meta>())
+            .item(fail_schema::<C, Meta>())
+            .discriminator(Some(Discriminator::new("type")))
+            .into()
+    }
+}
+
+impl<D, C, Meta> ToSchema for Brest<D, C, Meta>
+where
+    D: Serialize + ToSchema,
+    C: ToSchema,
+    Meta: ToSchema,
+{
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("Brest_{}_{}_{}", D::name(), C::name(), Meta::name()))
+    }
+
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        D::schemas(schemas);
+        C::schemas(schemas);
+        Meta::schemas(schemas);
+    }
+}
+
+fn json_response(description: &str, schema: Schema) -> RefOr<Response> {
+    ResponseBuilder::new()
+        .description(description)
+        .content(JSON, Content::new(Some(schema)))
+        .into()
+}
+
+/// Documents a handler returning `Brest<D, C, Meta>`: the success as `200`, fails as `4XX`
+/// and errors as `5XX`.
+impl<D, C, Meta> IntoResponses for Brest<D, C, Meta>
+where
+    D: Serialize + ToSchema,
+    C: ToSchema,
+    Meta: ToSchema,
+{
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        BTreeMap::from([
+            ("200".to_string(), json_response("Success", success_schema::<D, Meta>())),
+            ("4XX".to_string(), json_response("Fail", fail_schema::<C, Meta>())),
+            ("5XX".to_string(), json_response("Error", error_schema::<C, Meta>())),
+        ])
+    }
+}
+
+#[cfg(feature = "axum")]
+mod extractors {
+    use super::*;
+    use crate::extractors::{
+        Bytes, Extension, Form, IfMatch, IfUnmodifiedSince, Json, MatchedPath, Pagination, Path,
+        Query, RawForm, RawPathParams, RequestId,
+    };
+
+    /// Builds the responses of an extractor rejecting with `Brest<(), u32, ()>`.
+    fn rejections(fails: &[(u16, &str)]) -> BTreeMap<String, RefOr<Response>> {
+        fails
+            .iter()
+            .map(|(status, description)| {
+                (status.to_string(), json_response(description, fail_schema::<u32, ()>()))
+            })
+            .collect()
+    }
+
+    const BODY: [(u16, &str); 2] = [
+        (400, "The request body could not be read"),
+        (413, "The request body is too large"),
+    ];
+
+    impl<T> IntoResponses for Json<T> {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[
+                (400, "The request body could not be read or is not valid JSON"),
+                (413, "The request body is too large"),
+                (415, "Missing `application/json` content type"),
+                (422, "The request body doesn't match the expected shape"),
+            ])
+        }
+    }
+
+    impl<T> IntoResponses for Form<T> {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[
+                (400, "The form could not be read or deserialized"),
+                (413, "The request body is too large"),
+                (415, "Missing `application/x-www-form-urlencoded` content type"),
+                (422, "The form body doesn't match the expected shape"),
+            ])
+        }
+    }
+
+    impl IntoResponses for RawForm {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            let mut responses = rejections(&BODY);
+            responses.extend(rejections(&[(
+                415,
+                "Missing `application/x-www-form-urlencoded` content type",
+            )]));
+            responses
+        }
+    }
+
+    impl IntoResponses for Bytes {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&BODY)
+        }
+    }
+
+    impl<T> IntoResponses for Path<T> {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[
+                (400, "A path parameter could not be deserialized"),
+                (500, "The route has no path parameters to extract"),
+            ])
+        }
+    }
+
+    impl IntoResponses for RawPathParams {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[
+                (400, "A path parameter is not valid UTF-8"),
+                (500, "The route has no path parameters to extract"),
+            ])
+        }
+    }
+
+    impl<T> IntoResponses for Query<T> {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[(400, "The query string could not be deserialized")])
+        }
+    }
+
+    impl IntoResponses for Pagination {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[(400, "Invalid `page` or `per_page`")])
+        }
+    }
+
+    impl<T> IntoResponses for Extension<T> {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[(500, "The request extension is missing")])
+        }
+    }
+
+    impl IntoResponses for MatchedPath {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[(500, "No route matched the request")])
+        }
+    }
+
+    impl IntoResponses for IfMatch {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[
+                (400, "The `If-Match` header is malformed"),
+                (412, "The `If-Match` header doesn't match the current entity tag"),
+                (428, "Missing `If-Match` header"),
+            ])
+        }
+    }
+
+    impl IntoResponses for IfUnmodifiedSince {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            rejections(&[
+                (400, "The `If-Unmodified-Since` header is malformed"),
+                (412, "The resource was modified after `If-Unmodified-Since`"),
+                (428, "Missing `If-Unmodified-Since` header"),
+            ])
+        }
+    }
+
+    impl IntoResponses for RequestId {
+        fn responses() -> BTreeMap<String, RefOr<Response>> {
+            BTreeMap::from([(
+                "500".to_string(),
+                json_response(
+                    "`RequestIdLayer` is not installed",
+                    error_schema::<u32, ()>(),
+                ),
+            )])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json<T: Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_brest_schema() {
+        let schema = to_json(&Brest::<String, u32>::schema());
+        assert_eq!(schema["discriminator"]["propertyName"], "type");
+
+        let variants = schema["oneOf"].as_array().unwrap();
+        let kinds: Vec<_> = variants
+            .iter()
+            .map(|variant| variant["properties"]["type"]["enum"][0].clone())
+            .collect();
+        assert_eq!(kinds, ["success", "error", "fail"]);
+        assert_eq!(variants[0]["properties"]["data"]["type"], "string");
+        assert_eq!(variants[2]["properties"]["code"]["type"], "integer");
+        assert_eq!(variants[2]["required"], serde_json::json!(["type", "message"]));
+    }
+
+    #[test]
+    fn test_brest_responses() {
+        let responses = Brest::<String>::responses();
+        let keys: Vec<_> = responses.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["200", "4XX", "5XX"]);
+
+        let fail = to_json(&responses["4XX"]);
+        assert_eq!(
+            fail["content"]["application/json"]["schema"]["properties"]["type"]["enum"][0],
+            "fail"
+        );
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn test_extractor_responses() {
+        use crate::extractors::{IfMatch, Json};
+
+        let keys: Vec<_> = Json::<()>::responses().into_keys().collect();
+        assert_eq!(keys, ["400", "413", "415", "422"]);
+
+        let responses = IfMatch::responses();
+        let keys: Vec<_> = responses.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["400", "412", "428"]);
+    }
+}
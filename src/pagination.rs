@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+use crate::Brest;
+
+/// A page of items, used as the `data` of a successful list response.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "ts", derive(TS))]
+#[derive(Serialize, Deserialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional, type = "number"))]
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional, type = "number"))]
+    pub page: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional, type = "number"))]
+    pub per_page: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional))]
+    pub next_cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional))]
+    pub next: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional))]
+    pub prev: Option<String>,
+}
+
+impl<T> Paginated<T> {
+    /// A page addressed by page number, `page` starting at 1.
+    pub fn offset(items: Vec<T>, page: u64, per_page: u64, total: Option<u64>) -> Self {
+        Self {
+            items,
+            total,
+            page: Some(page),
+            per_page: Some(per_page),
+            next_cursor: None,
+            next: None,
+            prev: None,
+        }
+    }
+
+    /// A page addressed by an opaque cursor. `next_cursor` is `None` on the last page.
+    pub fn cursor(items: Vec<T>, next_cursor: Option<String>) -> Self {
+        Self {
+            items,
+            total: None,
+            page: None,
+            per_page: None,
+            next_cursor,
+            next: None,
+            prev: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_links(mut self, next: Option<String>, prev: Option<String>) -> Self {
+        self.next = next;
+        self.prev = prev;
+        self
+    }
+
+    /// Whether a page after this one exists.
+    ///
+    /// For offset pages without a `total`, a full page is assumed to have a successor.
+    pub fn has_next(&self) -> bool {
+        let Some(page) = self.page else {
+            return self.next_cursor.is_some();
+        };
+        let per_page = self.per_page.unwrap_or(0);
+        match self.total {
+            Some(total) => page.saturating_mul(per_page) < total,
+            None => per_page > 0 && self.items.len() as u64 >= per_page,
+        }
+    }
+
+    pub fn has_prev(&self) -> bool {
+        self.page.is_some_and(|page| page > 1)
+    }
+}
+
+impl<T: Serialize, C, Meta> Brest<Paginated<T>, C, Meta> {
+    pub fn page(items: Vec<T>, page: u64, per_page: u64, total: Option<u64>) -> Self {
+        Self::success(Paginated::offset(items, page, per_page, total))
+    }
+
+    pub fn cursor_page(items: Vec<T>, next_cursor: Option<String>) -> Self {
+        Self::success(Paginated::cursor(items, next_cursor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_serialization() {
+        let brest = Brest::<Paginated<u32>>::page(vec![1, 2], 1, 2, Some(3));
+        let json = serde_json::to_string(&brest).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"success","data":{"items":[1,2],"total":3,"page":1,"per_page":2}}"#
+        );
+    }
+
+    #[test]
+    fn test_cursor_page_serialization() {
+        let brest = Brest::<Paginated<u32>>::cursor_page(vec![1], Some("abc".to_string()));
+        let json = serde_json::to_string(&brest).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"success","data":{"items":[1],"next_cursor":"abc"}}"#
+        );
+    }
+
+    #[test]
+    fn test_has_next() {
+        assert!(Paginated::offset(vec![1, 2], 1, 2, Some(3)).has_next());
+        assert!(!Paginated::offset(vec![3], 2, 2, Some(3)).has_next());
+        assert!(Paginated::offset(vec![1, 2], 1, 2, None).has_next());
+        assert!(!Paginated::offset(vec![1], 1, 2, None).has_next());
+        assert!(!Paginated::<u32>::cursor(vec![], None).has_next());
+    }
+
+    #[test]
+    fn test_has_prev() {
+        assert!(!Paginated::offset(vec![1], 1, 2, None).has_prev());
+        assert!(Paginated::offset(vec![1], 2, 2, None).has_prev());
+    }
+}
@@ -0,0 +1,180 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde::Serialize;
+
+use crate::middleware::is_json;
+use crate::Brest;
+
+/// Codes used by the responses installed with [`BrestRouterExt::brest_defaults_with`].
+#[derive(Debug, Clone)]
+pub struct BrestDefaults<C = u32> {
+    pub not_found: Option<C>,
+    pub method_not_allowed: Option<C>,
+    pub payload_too_large: Option<C>,
+}
+
+impl<C> BrestDefaults<C> {
+    pub fn new() -> Self {
+        Self {
+            not_found: None,
+            method_not_allowed: None,
+            payload_too_large: None,
+        }
+    }
+
+    #[must_use]
+    pub fn not_found(mut self, code: C) -> Self {
+        self.not_found = Some(code);
+        self
+    }
+
+    #[must_use]
+    pub fn method_not_allowed(mut self, code: C) -> Self {
+        self.method_not_allowed = Some(code);
+        self
+    }
+
+    #[must_use]
+    pub fn payload_too_large(mut self, code: C) -> Self {
+        self.payload_too_large = Some(code);
+        self
+    }
+}
+
+impl<C> Default for BrestDefaults<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn fail<C: Serialize>(message: &str, code: Option<C>, status: StatusCode) -> Response {
+    match code {
+        Some(code) => Brest::<(), C>::fail_code_status(message, code, status),
+        None => Brest::<(), C>::fail_status(message, status),
+    }
+    .into_response()
+}
+
+/// Installs Brest responses for the cases axum answers with plain text bodies.
+pub trait BrestRouterExt: Sized {
+    /// Same as [`BrestRouterExt::brest_defaults_with`] without codes.
+    fn brest_defaults(self) -> Self {
+        self.brest_defaults_with(BrestDefaults::<u32>::new())
+    }
+
+    /// Installs a 404 fallback, a 405 fallback and rewrites plain text 413 responses
+    /// produced by `DefaultBodyLimit`.
+    ///
+    /// The 405 fallback only applies to routes registered before this call, so call it last.
+    /// axum keeps setting the `Allow` header on 405 responses.
+    fn brest_defaults_with<C>(self, defaults: BrestDefaults<C>) -> Self
+    where
+        C: Serialize + Clone + Send + Sync + 'static;
+}
+
+impl<S> BrestRouterExt for Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn brest_defaults_with<C>(self, defaults: BrestDefaults<C>) -> Self
+    where
+        C: Serialize + Clone + Send + Sync + 'static,
+    {
+        let BrestDefaults {
+            not_found,
+            method_not_allowed,
+            payload_too_large,
+        } = defaults;
+
+        self.fallback(move || async move { fail("Not found", not_found, StatusCode::NOT_FOUND) })
+            .method_not_allowed_fallback(move || async move {
+                fail(
+                    "Method not allowed",
+                    method_not_allowed,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                )
+            })
+            .layer(axum::middleware::map_response(move |response: Response| {
+                let code = payload_too_large.clone();
+                async move {
+                    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json(&response) {
+                        fail("Payload too large", code, StatusCode::PAYLOAD_TOO_LARGE)
+                    } else {
+                        response
+                    }
+                }
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, Bytes};
+    use axum::http::header;
+    use axum::extract::{DefaultBodyLimit, Request};
+    use axum::routing::{get, post};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(|| async { Brest::<u32>::success(1) }))
+            .route("/upload", post(|_: Bytes| async { Brest::<()>::success(()) }))
+            .layer(DefaultBodyLimit::max(4))
+            .brest_defaults_with(BrestDefaults::new().not_found(1).method_not_allowed(2).payload_too_large(3))
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_not_found() {
+        let response = app()
+            .oneshot(Request::builder().uri("/missing").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body(response).await, r#"{"type":"fail","message":"Not found","code":1}"#);
+    }
+
+    #[tokio::test]
+    async fn test_method_not_allowed() {
+        let response = app()
+            .oneshot(Request::builder().method("DELETE").uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET,HEAD");
+        assert_eq!(body(response).await, r#"{"type":"fail","message":"Method not allowed","code":2}"#);
+    }
+
+    #[tokio::test]
+    async fn test_payload_too_large() {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/upload")
+                    .body(Body::from("too large"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body(response).await, r#"{"type":"fail","message":"Payload too large","code":3}"#);
+    }
+
+    #[tokio::test]
+    async fn test_success_untouched() {
+        let response = Router::new()
+            .route("/", get(|| async { Brest::<u32>::success(1) }))
+            .brest_defaults()
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body(response).await, r#"{"type":"success","data":1}"#);
+    }
+}
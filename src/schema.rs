@@ -0,0 +1,241 @@
+use std::borrow::Cow;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{
+    InstanceType, Metadata, ObjectValidation, Schema, SchemaObject, SingleOrVec, SubschemaValidation,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::debug::DebugInfo;
+use crate::Brest;
+
+/// Builds one variant of the envelope: an object whose `type` is the constant `kind`.
+fn variant(
+    kind: &str,
+    title: &str,
+    properties: Vec<(&str, Schema, bool)>,
+    example: Value,
+) -> Schema {
+    let mut object = ObjectValidation::default();
+    object.required.insert("type".to_string());
+    object.properties.insert(
+        "type".to_string(),
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            const_value: Some(json!(kind)),
+            ..Default::default()
+        }
+        .into(),
+    );
+    for (name, schema, required) in properties {
+        if required {
+            object.required.insert(name.to_string());
+        }
+        object.properties.insert(name.to_string(), schema);
+    }
+
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            title: Some(title.to_string()),
+            examples: vec![example],
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(object)),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// A value for `data` in the success example: the first example of `schema`, or else an empty
+/// value of its type.
+fn example_data(gen: &SchemaGenerator, schema: &Schema) -> Value {
+    let Schema::Object(object) = gen.dereference(schema).unwrap_or(schema) else {
+        return Value::Null;
+    };
+    if let Some(example) = object.metadata.as_ref().and_then(|metadata| metadata.examples.first()) {
+        return example.clone();
+    }
+
+    let instance_type = match &object.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => Some(**instance_type),
+        Some(SingleOrVec::Vec(instance_types)) => instance_types.first().copied(),
+        None => None,
+    };
+    match instance_type {
+        Some(InstanceType::Object) => json!({}),
+        Some(InstanceType::Array) => json!([]),
+        Some(InstanceType::String) => json!(""),
+        Some(InstanceType::Integer | InstanceType::Number) => json!(0),
+        Some(InstanceType::Boolean) => json!(false),
+        Some(InstanceType::Null) | None => Value::Null,
+    }
+}
+
+/// The schema of what `Brest` puts on the wire: a `oneOf` over the three variants,
+/// discriminated by `type`. `code`, `debug` and `meta` are only present when set.
+impl<D, C, Meta> JsonSchema for Brest<D, C, Meta>
+where
+    D: Serialize + JsonSchema,
+    C: JsonSchema,
+    Meta: JsonSchema,
+{
+    fn schema_name() -> String {
+        format!(
+            "Brest_for_{}_and_{}_and_{}",
+            D::schema_name(),
+            C::schema_name(),
+            Meta::schema_name()
+        )
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        Cow::Owned(format!(
+            "brest::Brest<{}, {}, {}>",
+            D::schema_id(),
+            C::schema_id(),
+            Meta::schema_id()
+        ))
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let failure = |gen: &mut SchemaGenerator, kind: &str, title: &str, message: &str| {
+            variant(
+                kind,
+                title,
+                vec![
+                    ("message", gen.subschema_for::<String>(), true),
+                    ("code", gen.subschema_for::<C>(), false),
+                    ("debug", gen.subschema_for::<DebugInfo>(), false),
+                    ("meta", gen.subschema_for::<Meta>(), false),
+                ],
+                json!({ "type": kind, "message": message }),
+            )
+        };
+
+        let data = gen.subschema_for::<D>();
+        let example = json!({ "type": "success", "data": example_data(gen, &data) });
+        let success = variant(
+            "success",
+            "Success",
+            vec![("data", data, true), ("meta", gen.subschema_for::<Meta>(), false)],
+            example,
+        );
+        let error = failure(gen, "error", "Error", "Internal server error");
+        let fail = failure(gen, "fail", "Fail", "Not found");
+
+        let mut schema = SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                one_of: Some(vec![success, error, fail]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        schema
+            .extensions
+            .insert("discriminator".to_string(), json!({ "propertyName": "type" }));
+        schema.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::Value;
+
+    type Envelope = Brest<Vec<u32>, String, Value>;
+
+    fn full_debug() -> DebugInfo {
+        DebugInfo {
+            causes: vec!["cause".to_string()],
+            backtrace: Some("backtrace".to_string()),
+        }
+    }
+
+    /// Every shape the serializers can produce, with and without optional fields.
+    fn samples() -> Vec<Envelope> {
+        vec![
+            Brest::success(vec![1]),
+            Brest::success(vec![1]).with_meta(json!({ "page": 1 })),
+            Brest::error("boom"),
+            Brest::error_code("boom", "E1".to_string())
+                .with_debug(full_debug())
+                .with_meta(json!({})),
+            Brest::fail("nope"),
+            Brest::fail_code("nope", "F1".to_string())
+                .with_debug(full_debug())
+                .with_meta(json!({})),
+        ]
+    }
+
+    /// The samples as emitted by the serde derive and, with `axum`, by `IntoResponse`.
+    fn wire() -> Vec<Value> {
+        let values = samples()
+            .into_iter()
+            .map(|brest| serde_json::to_value(brest).unwrap());
+        #[cfg(feature = "axum")]
+        let values = values.chain(
+            samples()
+                .into_iter()
+                .map(|brest| serde_json::to_value(crate::BrestResponse(brest)).unwrap()),
+        );
+        values.collect()
+    }
+
+    fn schema() -> Value {
+        let schema = SchemaGenerator::default().into_root_schema_for::<Envelope>();
+        serde_json::to_value(schema).unwrap()
+    }
+
+    #[test]
+    fn test_schema_shape() {
+        let schema = schema();
+        assert_eq!(schema["discriminator"]["propertyName"], "type");
+
+        let variants = schema["oneOf"].as_array().unwrap();
+        let titles: Vec<_> = variants.iter().map(|v| v["title"].clone()).collect();
+        assert_eq!(titles, ["Success", "Error", "Fail"]);
+        assert_eq!(variants[0]["examples"][0], json!({ "type": "success", "data": [] }));
+        assert_eq!(variants[1]["examples"][0], json!({ "type": "error", "message": "Internal server error" }));
+        assert_eq!(variants[2]["properties"]["code"]["type"], "string");
+        assert_eq!(variants[2]["required"], json!(["message", "type"]));
+    }
+
+    #[test]
+    fn test_schema_matches_serializer() {
+        let schema = schema();
+        let variants = schema["oneOf"].as_array().unwrap();
+
+        let mut seen = std::collections::BTreeMap::<String, Vec<String>>::new();
+        for value in wire() {
+            let object = value.as_object().unwrap();
+            let variant = variants
+                .iter()
+                .find(|v| v["properties"]["type"]["const"] == object["type"])
+                .unwrap_or_else(|| panic!("no variant for {}", value));
+            let properties = variant["properties"].as_object().unwrap();
+
+            for key in object.keys() {
+                assert!(properties.contains_key(key), "`{}` missing from schema of {}", key, value);
+            }
+            for required in variant["required"].as_array().unwrap() {
+                assert!(object.contains_key(required.as_str().unwrap()), "{} lacks {}", value, required);
+            }
+
+            seen.entry(object["type"].as_str().unwrap().to_string())
+                .or_default()
+                .extend(object.keys().cloned());
+        }
+
+        // Every property the schema declares is produced by some sample.
+        for variant in variants {
+            let kind = variant["properties"]["type"]["const"].as_str().unwrap();
+            for key in variant["properties"].as_object().unwrap().keys() {
+                assert!(seen[kind].contains(key), "`{}` of {} is never serialized", key, kind);
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! TypeScript bindings for the envelope, generated with [`ts_rs`].
+//!
+//! [`export`] writes `Brest.ts`, `ErrorFields.ts`, `DebugInfo.ts`, `Paginated.ts`, the code
+//! type `C` and [`TYPE_GUARDS`] as `guards.ts` into a directory.
+
+use std::path::Path;
+
+use ts_rs::{ExportError, TS};
+
+use crate::{Brest, ErrorFields, Paginated};
+
+/// Narrowing helpers over the `type` discriminator of `Brest`, written as `guards.ts`.
+pub const TYPE_GUARDS: &str = r#"// This file was generated by brest. Do not edit this file manually.
+import type { Brest } from "./Brest";
+
+export function isSuccess<D, C, Meta>(brest: Brest<D, C, Meta>): brest is Extract<Brest<D, C, Meta>, { "type": "success" }> {
+  return brest.type === "success";
+}
+
+export function isError<D, C, Meta>(brest: Brest<D, C, Meta>): brest is Extract<Brest<D, C, Meta>, { "type": "error" }> {
+  return brest.type === "error";
+}
+
+export function isFail<D, C, Meta>(brest: Brest<D, C, Meta>): brest is Extract<Brest<D, C, Meta>, { "type": "fail" }> {
+  return brest.type === "fail";
+}
+"#;
+
+/// Exports the envelope types, the code type `C` and the type guards into `dir`.
+///
+/// `C` is usually an enum of error codes deriving [`TS`]; with the default `u32` no extra
+/// file is written.
+pub fn export<C: TS + 'static>(dir: impl AsRef<Path>) -> Result<(), ExportError> {
+    let dir = dir.as_ref();
+    Brest::<(), C>::export_all_to(dir)?;
+    ErrorFields::<C>::export_all_to(dir)?;
+    Paginated::<()>::export_all_to(dir)?;
+    if C::output_path().is_some() {
+        C::export_all_to(dir)?;
+    }
+    std::fs::write(dir.join("guards.ts"), TYPE_GUARDS)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(decl: &str) -> Vec<&str> {
+        decl.split("\"type\": \"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect()
+    }
+
+    #[test]
+    fn test_decl() {
+        assert_eq!(
+            Brest::<()>::decl(),
+            "type Brest<D = null, C = number, Meta = null> = \
+             { \"type\": \"success\", data: D, meta?: Meta, } | \
+             { \"type\": \"error\", message: string, code?: C, debug?: DebugInfo, meta?: Meta, } | \
+             { \"type\": \"fail\", message: string, code?: C, debug?: DebugInfo, meta?: Meta, };"
+        );
+    }
+
+    #[test]
+    fn test_guards_cover_every_variant() {
+        let decl = Brest::<()>::decl();
+        let variants = tags(&decl);
+        assert_eq!(variants, ["success", "error", "fail"]);
+
+        for tag in &variants {
+            let name = format!("is{}{}", tag[..1].to_uppercase(), &tag[1..]);
+            assert!(TYPE_GUARDS.contains(&format!("export function {}<", name)), "missing {}", name);
+            assert!(TYPE_GUARDS.contains(&format!("brest.type === \"{}\"", tag)));
+        }
+        assert_eq!(tags(TYPE_GUARDS).len(), variants.len());
+    }
+
+    #[test]
+    fn test_export() {
+        #[derive(TS)]
+        #[allow(dead_code)]
+        enum Code {
+            NotFound,
+            Conflict,
+        }
+
+        let dir = std::env::temp_dir().join(format!("brest-ts-{}", std::process::id()));
+        export::<Code>(&dir).unwrap();
+        for file in ["Brest.ts", "ErrorFields.ts", "DebugInfo.ts", "Paginated.ts", "Code.ts", "guards.ts"] {
+            assert!(dir.join(file).exists(), "{} was not exported", file);
+        }
+        let code = std::fs::read_to_string(dir.join("Code.ts")).unwrap();
+        assert!(code.contains(r#"export type Code = "NotFound" | "Conflict";"#));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}